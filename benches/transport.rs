@@ -0,0 +1,159 @@
+//! Compares the throughput of the BOLT-8 transport when every packet allocates
+//! and rebuilds its cipher, as done by `encrypt_with_ad` and `decrypt_with_ad`,
+//! with the in-place encryption of the reader and the writer, whose messages are either
+//! copied out of the reader or borrowed from it.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lightning_client::{
+    bolt_8::{
+        crypto::{decrypt_with_ad, encrypt_with_ad},
+        protocol::{
+            ClientProtocol, Decrypted, EncryptedReader, EncryptedWriter, HandshakeTimeouts,
+            ServerProtocol,
+        },
+    },
+    signer::InMemorySigner,
+};
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use std::hint::black_box;
+
+// The lengths of the messages, from a ping to the largest message allowed.
+const LENGTHS: [usize; 3] = [32, 1024, 65535];
+
+// Performs the handshake in memory and returns the writer of the client and the reader of the server.
+fn transport() -> (EncryptedWriter, EncryptedReader) {
+    let server_sk = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let client_sk = SecretKey::from_slice(&[0x22; 32]).unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let (mut client_stream, mut server_stream) = tokio::io::duplex(1024);
+        let timeouts = HandshakeTimeouts::default();
+
+        let (client_proto, server_proto) = tokio::join!(
+            ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &server_sk))
+                .perform_handshake(
+                    &mut client_stream,
+                    InMemorySigner::new(client_sk),
+                    &timeouts
+                ),
+            ServerProtocol::new(server_sk).accept_handshake(&mut server_stream, &timeouts),
+        );
+
+        let (_, writer) = client_proto.unwrap().split();
+        let (reader, _) = server_proto.unwrap().0.split();
+
+        (writer, reader)
+    })
+}
+
+// Encrypts the packet the way the transport did before the ciphers were cached.
+fn encrypt_allocating(k: &[u8; 32], n: &mut u64, m: &[u8]) -> Vec<u8> {
+    let l = (m.len() as u16).to_be_bytes();
+
+    let mut lc = encrypt_with_ad(k, *n, &[], &l).unwrap();
+    let c = encrypt_with_ad(k, *n + 1, &[], m).unwrap();
+    *n += 2;
+
+    lc.extend_from_slice(&c);
+
+    lc
+}
+
+// Decrypts the packet the way the transport did before the ciphers were cached.
+fn decrypt_allocating(k: &[u8; 32], n: &mut u64, packet: &[u8]) -> Vec<u8> {
+    let (lc, c) = packet.split_at(18);
+
+    let l = decrypt_with_ad(k, *n, &[], lc).unwrap();
+    assert_eq!(u16::from_be_bytes([l[0], l[1]]) as usize + 16, c.len());
+
+    let m = decrypt_with_ad(k, *n + 1, &[], c).unwrap();
+    *n += 2;
+
+    m
+}
+
+fn encrypt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encrypt");
+    group.throughput(Throughput::Elements(1));
+
+    for len in LENGTHS {
+        let m = vec![0x42; len];
+
+        group.bench_with_input(BenchmarkId::new("allocating", len), &m, |b, m| {
+            let (k, mut n) = ([0x01; 32], 0);
+
+            b.iter(|| black_box(encrypt_allocating(&k, &mut n, m)));
+        });
+
+        group.bench_with_input(BenchmarkId::new("in_place", len), &m, |b, m| {
+            let (mut writer, _) = transport();
+            let mut buf = Vec::new();
+
+            b.iter(|| {
+                buf.clear();
+                writer.encrypt_into(m, &mut buf).unwrap();
+                black_box(&buf);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn round_trip(c: &mut Criterion) {
+    let mut group = c.benchmark_group("round_trip");
+    group.throughput(Throughput::Elements(1));
+
+    for len in LENGTHS {
+        let m = vec![0x42; len];
+
+        group.bench_with_input(BenchmarkId::new("allocating", len), &m, |b, m| {
+            let (k, mut sn, mut rn) = ([0x01; 32], 0, 0);
+
+            b.iter(|| {
+                let packet = encrypt_allocating(&k, &mut sn, m);
+                black_box(decrypt_allocating(&k, &mut rn, &packet));
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("in_place", len), &m, |b, m| {
+            let (mut writer, mut reader) = transport();
+            let mut buf = Vec::new();
+
+            b.iter(|| {
+                buf.clear();
+                writer.encrypt_into(m, &mut buf).unwrap();
+
+                match reader.decrypt(&buf).unwrap() {
+                    Decrypted::Message(m) => black_box(m),
+                    Decrypted::NeedMore(_) => unreachable!(),
+                };
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("borrowed", len), &m, |b, m| {
+            let (mut writer, mut reader) = transport();
+            let mut buf = Vec::new();
+
+            b.iter(|| {
+                buf.clear();
+                writer.encrypt_into(m, &mut buf).unwrap();
+
+                match reader.decrypt_in_place(&buf).unwrap() {
+                    Decrypted::Message(m) => black_box(m),
+                    Decrypted::NeedMore(_) => unreachable!(),
+                };
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, encrypt, round_trip);
+criterion_main!(benches);
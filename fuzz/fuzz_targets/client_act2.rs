@@ -0,0 +1,33 @@
+//! Processes the Act-2 message of the responder on the initiator side.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lightning_client::{
+    bolt_8::protocol::ClientProtocol,
+    signer::{InMemorySigner, NodeSigner},
+};
+use secp256k1::{PublicKey, SecretKey};
+use vectors::{LE_SK, LS_SK, RS_PK};
+
+mod vectors;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(rm) = <&[u8; 50]>::try_from(data) else {
+        return;
+    };
+
+    let rs_pk = PublicKey::from_slice(&RS_PK).unwrap();
+    let ls_sk = SecretKey::from_slice(&LS_SK).unwrap();
+    let le_sk = SecretKey::from_slice(&LE_SK).unwrap();
+
+    let signer = InMemorySigner::new(ls_sk);
+    let client_proto = ClientProtocol::new(rs_pk)
+        .into_next_phase_with_ephemeral_key(&signer, le_sk)
+        .unwrap();
+
+    if let Ok(client_proto) = client_proto.process_inbound(rm) {
+        let se = signer.ecdh(&client_proto.remote_ephemeral_key()).unwrap();
+        let _ = client_proto.process_ecdh(se);
+    }
+});
@@ -0,0 +1,44 @@
+//! Decrypts the messages of the initiator on the responder side, received in chunks.
+//!
+//! The first byte sets the length of the chunks, the remaining bytes are the received stream.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lightning_client::bolt_8::protocol::{Decrypted, ServerProtocol};
+use secp256k1::SecretKey;
+use vectors::{ACT_1, ACT_3, RE_SK, RS_SK};
+
+mod vectors;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&chunk_len, stream)) = data.split_first() else {
+        return;
+    };
+
+    let ls_sk = SecretKey::from_slice(&RS_SK).unwrap();
+    let le_sk = SecretKey::from_slice(&RE_SK).unwrap();
+
+    let (mut reader, _) = ServerProtocol::new(ls_sk)
+        .process_inbound(&ACT_1)
+        .unwrap()
+        .into_next_phase_with_ephemeral_key(le_sk)
+        .unwrap()
+        .process_inbound(&ACT_3)
+        .unwrap()
+        .into_next_phase()
+        .split();
+
+    for chunk in stream.chunks(chunk_len as usize + 1) {
+        let mut chunk = chunk;
+
+        // The chunk may complete several messages, which are decrypted one after the other.
+        loop {
+            match reader.decrypt(chunk) {
+                Ok(Decrypted::Message(_)) => chunk = &[],
+                Ok(Decrypted::NeedMore(_)) => break,
+                Err(_) => return,
+            }
+        }
+    }
+});
@@ -0,0 +1,29 @@
+//! Processes the Act-3 message of the initiator on the responder side.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lightning_client::bolt_8::protocol::ServerProtocol;
+use secp256k1::SecretKey;
+use vectors::{ACT_1, RE_SK, RS_SK};
+
+mod vectors;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(rm) = <&[u8; 66]>::try_from(data) else {
+        return;
+    };
+
+    let ls_sk = SecretKey::from_slice(&RS_SK).unwrap();
+    let le_sk = SecretKey::from_slice(&RE_SK).unwrap();
+
+    let server_proto = ServerProtocol::new(ls_sk)
+        .process_inbound(&ACT_1)
+        .unwrap()
+        .into_next_phase_with_ephemeral_key(le_sk)
+        .unwrap();
+
+    if let Ok(server_proto) = server_proto.process_inbound(rm) {
+        let _ = server_proto.into_next_phase();
+    }
+});
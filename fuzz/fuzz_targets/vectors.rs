@@ -0,0 +1,32 @@
+//! The BOLT-8 test vectors, so that the seeds of the corpus are valid messages.
+//!
+//! See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+// Every target only uses the vectors of its own side.
+#![allow(dead_code)]
+
+use hex_literal::hex;
+
+/// The static public key of the responder.
+pub const RS_PK: [u8; 33] =
+    hex!("028d7500dd4c12685d1f568b4c2b5048e8534b873319f3a8daa612b469132ec7f7");
+
+/// The static secret key of the responder.
+pub const RS_SK: [u8; 32] =
+    hex!("2121212121212121212121212121212121212121212121212121212121212121");
+
+/// The ephemeral secret key of the responder.
+pub const RE_SK: [u8; 32] =
+    hex!("2222222222222222222222222222222222222222222222222222222222222222");
+
+/// The static secret key of the initiator.
+pub const LS_SK: [u8; 32] =
+    hex!("1111111111111111111111111111111111111111111111111111111111111111");
+
+/// The ephemeral secret key of the initiator.
+pub const LE_SK: [u8; 32] =
+    hex!("1212121212121212121212121212121212121212121212121212121212121212");
+
+pub const ACT_1: [u8; 50] = hex!("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+pub const ACT_3: [u8; 66] = hex!("00b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba");
@@ -0,0 +1,27 @@
+use crate::bolt_1::tlv::TlvError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MessageError {
+    #[error("The message is too short to contain its type")]
+    MissingType,
+
+    #[error("Unexpected end of message: want {want} bytes, got {got}")]
+    UnexpectedEnd { want: usize, got: usize },
+
+    #[error("The '{field}' field is too long to be encoded: {len} bytes")]
+    FieldTooLong { field: &'static str, len: usize },
+
+    #[error("The '{field}' field has an invalid length: {len} bytes")]
+    InvalidFieldLength { field: &'static str, len: usize },
+
+    #[error("The TLV stream of the message is invalid")]
+    InvalidTlvStream { source: eyre::Report },
+}
+
+impl From<TlvError> for MessageError {
+    fn from(e: TlvError) -> Self {
+        Self::InvalidTlvStream {
+            source: eyre::Report::new(e),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{
+    bolt_1::{
+        message::{
+            reader::{write_u16_prefixed, Reader},
+            MessageError,
+        },
+        tlv::TlvStream,
+    },
+    bolt_9::features::Features,
+};
+
+/// The `init` message, used to tell the remote node which features are supported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Init {
+    /// The legacy global features.
+    pub global_features: Features,
+
+    /// The features supported or required by the node.
+    pub features: Features,
+
+    /// The TLV stream extending the message.
+    pub tlvs: TlvStream,
+}
+
+impl Init {
+    /// The type of the message.
+    pub const TYPE: u16 = 16;
+
+    /// The types of the TLV records known to be part of the message:
+    ///     - 1 for the chains the node is interested in;
+    ///     - 3 for the address of the remote node as seen by the sender;
+    pub const KNOWN_TLV_TYPES: [u64; 2] = [1, 3];
+
+    /// Returns the chain hashes of the networks the node is interested in, if any.
+    pub fn networks(&self) -> Result<Option<Vec<[u8; 32]>>, MessageError> {
+        let Some(value) = self.tlvs.get(1) else {
+            return Ok(None);
+        };
+
+        if value.len() % 32 != 0 {
+            return Err(MessageError::InvalidFieldLength {
+                field: "networks",
+                len: value.len(),
+            });
+        }
+
+        Ok(Some(
+            value
+                .chunks_exact(32)
+                .map(|x| x.try_into().unwrap())
+                .collect(),
+        ))
+    }
+
+    /// Sets the chain hashes of the networks the node is interested in.
+    pub fn set_networks(&mut self, networks: &[[u8; 32]]) {
+        self.tlvs.insert(1, networks.concat());
+    }
+
+    /// Returns the features of the node, combining the global features and the features.
+    pub fn combined_features(&self) -> Features {
+        self.global_features.union(&self.features)
+    }
+
+    /// Decodes the message from its payload.
+    pub(super) fn decode(payload: &[u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader::new(payload);
+
+        let global_features = Features::from_bytes(reader.read_u16_prefixed()?);
+        let features = Features::from_bytes(reader.read_u16_prefixed()?);
+        let tlvs = TlvStream::decode(reader.into_remaining(), &Self::KNOWN_TLV_TYPES)?;
+
+        Ok(Self {
+            global_features,
+            features,
+            tlvs,
+        })
+    }
+
+    /// Encodes the payload of the message.
+    pub(super) fn encode(&self, buf: &mut Vec<u8>) -> Result<(), MessageError> {
+        write_u16_prefixed(buf, "globalfeatures", &self.global_features.to_bytes())?;
+        write_u16_prefixed(buf, "features", &self.features.to_bytes())?;
+        self.tlvs.encode(buf);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn it_decodes_and_encodes_the_message() {
+        let payload = hex!("000000060a088288a698012006226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f");
+
+        let init = Init::decode(&payload).unwrap();
+
+        assert_eq! { init.global_features, Features::default() };
+        assert_eq! { init.features, Features::from_bytes(&hex!("0a088288a698")) };
+        assert_eq! { init.tlvs, TlvStream::decode(&hex!("012006226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f"), &[]).unwrap() };
+
+        let mut buf = Vec::new();
+        init.encode(&mut buf).unwrap();
+
+        assert_eq! { buf, payload };
+    }
+
+    #[test]
+    fn it_returns_the_networks() {
+        let mut init = Init::default();
+
+        assert_eq! { init.networks().unwrap(), None };
+
+        init.set_networks(&[[0x01; 32], [0x02; 32]]);
+
+        assert_eq! { init.networks().unwrap(), Some(vec![[0x01; 32], [0x02; 32]]) };
+
+        init.tlvs.insert(1, vec![0x01; 33]);
+
+        assert!(matches!(
+            init.networks(),
+            Err(MessageError::InvalidFieldLength {
+                field: "networks",
+                len: 33
+            })
+        ));
+    }
+
+    #[test]
+    fn it_returns_the_union_of_the_features() {
+        let init = Init {
+            global_features: Features::from_bytes(&hex!("2200")),
+            features: Features::from_bytes(&hex!("080281")),
+            tlvs: TlvStream::default(),
+        };
+
+        assert_eq! { init.combined_features().bits(), vec![0, 7, 9, 13, 19] };
+    }
+
+    #[test]
+    fn it_fails_on_truncated_features() {
+        let payload = hex!("0000000a0a08");
+
+        assert!(matches!(
+            Init::decode(&payload),
+            Err(MessageError::UnexpectedEnd { want: 10, got: 2 })
+        ));
+    }
+
+    #[test]
+    fn it_fails_on_unknown_even_tlv_records() {
+        let payload = hex!("000000000200");
+
+        assert!(matches!(
+            Init::decode(&payload),
+            Err(MessageError::InvalidTlvStream { .. })
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+//! This module defines the messages of the BOLT-1 protocol.
+//!
+//! Every message consists of a 2-byte big-endian type followed by its payload.
+
+mod error;
+mod error_message;
+mod init;
+mod ping;
+mod pong;
+mod reader;
+mod warning;
+
+pub use self::error::MessageError;
+pub use self::error_message::ErrorMessage;
+pub use self::init::Init;
+pub use self::ping::Ping;
+pub use self::pong::Pong;
+pub use self::warning::Warning;
+
+/// A decoded Lightning message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Init(Init),
+    Error(ErrorMessage),
+    Warning(Warning),
+    Ping(Ping),
+    Pong(Pong),
+
+    /// A message of an unknown odd type, which can be safely ignored.
+    UnknownOdd {
+        message_type: u16,
+        payload: Vec<u8>,
+    },
+
+    /// A message of an unknown even type, which requires the connection to be failed.
+    UnknownEven {
+        message_type: u16,
+        payload: Vec<u8>,
+    },
+}
+
+impl Message {
+    /// Decodes a message from the bytes passed.
+    pub fn decode(bytes: &[u8]) -> Result<Self, MessageError> {
+        if bytes.len() < 2 {
+            return Err(MessageError::MissingType);
+        }
+
+        let (message_type, payload) = bytes.split_at(2);
+        let message_type = u16::from_be_bytes([message_type[0], message_type[1]]);
+
+        let message = match message_type {
+            Init::TYPE => Self::Init(Init::decode(payload)?),
+            ErrorMessage::TYPE => Self::Error(ErrorMessage::decode(payload)?),
+            Warning::TYPE => Self::Warning(Warning::decode(payload)?),
+            Ping::TYPE => Self::Ping(Ping::decode(payload)?),
+            Pong::TYPE => Self::Pong(Pong::decode(payload)?),
+            _ if message_type % 2 == 1 => Self::UnknownOdd {
+                message_type,
+                payload: payload.to_vec(),
+            },
+            _ => Self::UnknownEven {
+                message_type,
+                payload: payload.to_vec(),
+            },
+        };
+
+        Ok(message)
+    }
+
+    /// Encodes the message into bytes.
+    pub fn encode(&self) -> Result<Vec<u8>, MessageError> {
+        let mut buf = Vec::new();
+
+        buf.extend_from_slice(&self.message_type().to_be_bytes());
+
+        match self {
+            Self::Init(x) => x.encode(&mut buf)?,
+            Self::Error(x) => x.encode(&mut buf)?,
+            Self::Warning(x) => x.encode(&mut buf)?,
+            Self::Ping(x) => x.encode(&mut buf)?,
+            Self::Pong(x) => x.encode(&mut buf)?,
+            Self::UnknownOdd { payload, .. } | Self::UnknownEven { payload, .. } => {
+                buf.extend_from_slice(payload)
+            }
+        }
+
+        Ok(buf)
+    }
+
+    /// Returns the type of the message.
+    pub fn message_type(&self) -> u16 {
+        match self {
+            Self::Init(_) => Init::TYPE,
+            Self::Error(_) => ErrorMessage::TYPE,
+            Self::Warning(_) => Warning::TYPE,
+            Self::Ping(_) => Ping::TYPE,
+            Self::Pong(_) => Pong::TYPE,
+            Self::UnknownOdd { message_type, .. } | Self::UnknownEven { message_type, .. } => {
+                *message_type
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bolt_1::tlv::TlvStream, bolt_9::features::Features};
+    use hex_literal::hex;
+
+    #[test]
+    fn it_decodes_and_encodes_the_init_message() {
+        // The init message received from a public node.
+        let bytes = hex!("001000000006a088288a698101206fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000");
+
+        let message = Message::decode(&bytes).unwrap();
+
+        assert_eq! {
+            message,
+            Message::Init(Init {
+                global_features: Features::default(),
+                features: Features::from_bytes(&hex!("a088288a6981")),
+                tlvs: TlvStream::decode(&hex!("01206fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000"), &[]).unwrap(),
+            })
+        };
+        assert_eq! { message.encode().unwrap(), bytes };
+    }
+
+    #[test]
+    fn it_decodes_and_encodes_the_ping_message() {
+        let bytes = hex!("0012000a0000");
+
+        let message = Message::decode(&bytes).unwrap();
+
+        assert_eq! { message, Message::Ping(Ping { num_pong_bytes: 10, ignored: vec![] }) };
+        assert_eq! { message.encode().unwrap(), bytes };
+    }
+
+    #[test]
+    fn it_decodes_unknown_messages_by_their_parity() {
+        let message = Message::decode(&hex!("8001aabb")).unwrap();
+
+        assert_eq! { message, Message::UnknownOdd { message_type: 0x8001, payload: vec![0xaa, 0xbb] } };
+        assert_eq! { message.encode().unwrap(), hex!("8001aabb") };
+
+        let message = Message::decode(&hex!("8000aabb")).unwrap();
+
+        assert_eq! { message, Message::UnknownEven { message_type: 0x8000, payload: vec![0xaa, 0xbb] } };
+        assert_eq! { message.encode().unwrap(), hex!("8000aabb") };
+    }
+
+    #[test]
+    fn it_fails_without_a_type() {
+        assert!(matches!(
+            Message::decode(&[0x00]),
+            Err(MessageError::MissingType)
+        ));
+    }
+}
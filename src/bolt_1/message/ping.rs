@@ -0,0 +1,59 @@
+use crate::bolt_1::message::{
+    reader::{write_u16_prefixed, Reader},
+    MessageError,
+};
+
+/// The `ping` message, used to check that the remote node is still alive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ping {
+    /// The number of bytes the remote node should respond with in the `pong` message.
+    pub num_pong_bytes: u16,
+
+    /// The padding, which should be set to zeros.
+    pub ignored: Vec<u8>,
+}
+
+impl Ping {
+    /// The type of the message.
+    pub const TYPE: u16 = 18;
+
+    /// Decodes the message from its payload.
+    pub(super) fn decode(payload: &[u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader::new(payload);
+
+        let num_pong_bytes = reader.read_u16()?;
+        let ignored = reader.read_u16_prefixed()?.to_vec();
+
+        Ok(Self {
+            num_pong_bytes,
+            ignored,
+        })
+    }
+
+    /// Encodes the payload of the message.
+    pub(super) fn encode(&self, buf: &mut Vec<u8>) -> Result<(), MessageError> {
+        buf.extend_from_slice(&self.num_pong_bytes.to_be_bytes());
+        write_u16_prefixed(buf, "ignored", &self.ignored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn it_decodes_and_encodes_the_message() {
+        let payload = hex!("000400020000");
+
+        let ping = Ping::decode(&payload).unwrap();
+
+        assert_eq! { ping.num_pong_bytes, 4 };
+        assert_eq! { ping.ignored, [0; 2] };
+
+        let mut buf = Vec::new();
+        ping.encode(&mut buf).unwrap();
+
+        assert_eq! { buf, payload };
+    }
+}
@@ -0,0 +1,50 @@
+use crate::bolt_1::message::{
+    reader::{write_u16_prefixed, Reader},
+    MessageError,
+};
+
+/// The `pong` message, sent in response to a `ping` message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pong {
+    /// The padding, which should be set to zeros.
+    pub ignored: Vec<u8>,
+}
+
+impl Pong {
+    /// The type of the message.
+    pub const TYPE: u16 = 19;
+
+    /// Decodes the message from its payload.
+    pub(super) fn decode(payload: &[u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader::new(payload);
+
+        let ignored = reader.read_u16_prefixed()?.to_vec();
+
+        Ok(Self { ignored })
+    }
+
+    /// Encodes the payload of the message.
+    pub(super) fn encode(&self, buf: &mut Vec<u8>) -> Result<(), MessageError> {
+        write_u16_prefixed(buf, "ignored", &self.ignored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn it_decodes_and_encodes_the_message() {
+        let payload = hex!("000400000000");
+
+        let pong = Pong::decode(&payload).unwrap();
+
+        assert_eq! { pong.ignored, [0; 4] };
+
+        let mut buf = Vec::new();
+        pong.encode(&mut buf).unwrap();
+
+        assert_eq! { buf, payload };
+    }
+}
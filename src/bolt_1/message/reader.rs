@@ -0,0 +1,105 @@
+use crate::bolt_1::message::MessageError;
+
+/// A cursor over the payload of a message that reads its fields in order.
+pub struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new reader over the bytes passed.
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    /// Reads a big-endian 16-bit unsigned integer.
+    pub fn read_u16(&mut self) -> Result<u16, MessageError> {
+        let x = self.read_array::<2>()?;
+
+        Ok(u16::from_be_bytes(x))
+    }
+
+    /// Reads exactly `N` bytes.
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], MessageError> {
+        let x = self.read_bytes(N)?;
+
+        Ok(x.try_into().unwrap())
+    }
+
+    /// Reads exactly `n` bytes.
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], MessageError> {
+        if self.buf.len() < n {
+            return Err(MessageError::UnexpectedEnd {
+                want: n,
+                got: self.buf.len(),
+            });
+        }
+
+        let (x, rest) = self.buf.split_at(n);
+        self.buf = rest;
+
+        Ok(x)
+    }
+
+    /// Reads a 16-bit length followed by that many bytes.
+    pub fn read_u16_prefixed(&mut self) -> Result<&'a [u8], MessageError> {
+        let len = self.read_u16()?;
+
+        self.read_bytes(len as usize)
+    }
+
+    /// Returns the remaining bytes, consuming the reader.
+    pub fn into_remaining(self) -> &'a [u8] {
+        self.buf
+    }
+}
+
+/// Writes a 16-bit length followed by the bytes passed.
+pub fn write_u16_prefixed(
+    buf: &mut Vec<u8>,
+    field: &'static str,
+    x: &[u8],
+) -> Result<(), MessageError> {
+    let len: u16 = x.len().try_into().map_err(|_| MessageError::FieldTooLong {
+        field,
+        len: x.len(),
+    })?;
+
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(x);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_the_fields_in_order() {
+        let mut reader = Reader::new(&[0x01, 0x02, 0x00, 0x02, 0xaa, 0xbb, 0xcc]);
+
+        assert_eq! { reader.read_u16().unwrap(), 0x0102 };
+        assert_eq! { reader.read_u16_prefixed().unwrap(), &[0xaa, 0xbb] };
+        assert_eq! { reader.into_remaining(), &[0xcc] };
+    }
+
+    #[test]
+    fn it_fails_on_unexpected_end() {
+        let mut reader = Reader::new(&[0x00, 0x03, 0xaa]);
+
+        assert!(matches!(
+            reader.read_u16_prefixed(),
+            Err(MessageError::UnexpectedEnd { want: 3, got: 1 })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_fields_that_are_too_long() {
+        let mut buf = Vec::new();
+
+        assert!(matches!(
+            write_u16_prefixed(&mut buf, "data", &[0; u16::MAX as usize + 1]),
+            Err(MessageError::FieldTooLong { field: "data", .. })
+        ));
+    }
+}
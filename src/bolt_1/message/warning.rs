@@ -0,0 +1,59 @@
+use crate::bolt_1::message::{
+    reader::{write_u16_prefixed, Reader},
+    MessageError,
+};
+
+/// The `warning` message, used to tell the remote node about a non-fatal problem.
+///
+/// An all-zero channel id refers to all the channels with the remote node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// The channel the warning refers to.
+    pub channel_id: [u8; 32],
+
+    /// The data describing the warning, usually a printable string.
+    pub data: Vec<u8>,
+}
+
+impl Warning {
+    /// The type of the message.
+    pub const TYPE: u16 = 1;
+
+    /// Decodes the message from its payload.
+    pub(super) fn decode(payload: &[u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader::new(payload);
+
+        let channel_id = reader.read_array::<32>()?;
+        let data = reader.read_u16_prefixed()?.to_vec();
+
+        Ok(Self { channel_id, data })
+    }
+
+    /// Encodes the payload of the message.
+    pub(super) fn encode(&self, buf: &mut Vec<u8>) -> Result<(), MessageError> {
+        buf.extend_from_slice(&self.channel_id);
+        write_u16_prefixed(buf, "data", &self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn it_decodes_and_encodes_the_message() {
+        let payload =
+            hex!("0000000000000000000000000000000000000000000000000000000000000000000462616421");
+
+        let warning = Warning::decode(&payload).unwrap();
+
+        assert_eq! { warning.channel_id, [0; 32] };
+        assert_eq! { warning.data, b"bad!" };
+
+        let mut buf = Vec::new();
+        warning.encode(&mut buf).unwrap();
+
+        assert_eq! { buf, payload };
+    }
+}
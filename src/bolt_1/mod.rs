@@ -0,0 +1,7 @@
+//! This module is an implementation of the BOLT-1 protocol.
+//!
+//! Spec: <https://github.com/lightning/bolts/blob/master/01-messaging.md>
+
+pub mod message;
+pub mod network;
+pub mod tlv;
@@ -0,0 +1,78 @@
+use hex_literal::hex;
+use std::{fmt, str::FromStr};
+
+/// A Bitcoin network the node can operate on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+impl Network {
+    /// Returns the chain hash of the network, which is the hash of its genesis block
+    /// in the internal byte order.
+    pub fn chain_hash(&self) -> [u8; 32] {
+        match self {
+            Self::Bitcoin => {
+                hex!("6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000")
+            }
+            Self::Testnet => {
+                hex!("43497fd7f826957108f4a30fd9cec3aeba79972084e90ead01ea330900000000")
+            }
+            Self::Signet => {
+                hex!("f61eee3b63a380a477a063af32b2bbc97c9ff9f01f2c4225e973988108000000")
+            }
+            Self::Regtest => {
+                hex!("06226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bitcoin => write!(f, "bitcoin"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::Signet => write!(f, "signet"),
+            Self::Regtest => write!(f, "regtest"),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bitcoin" => Ok(Self::Bitcoin),
+            "testnet" => Ok(Self::Testnet),
+            "signet" => Ok(Self::Signet),
+            "regtest" => Ok(Self::Regtest),
+            _ => Err(format!(
+                "The '{s}' is not a known network. Expected one of: bitcoin, testnet, signet, regtest"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_and_displays_the_network() {
+        for network in [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            assert_eq! { network.to_string().parse::<Network>().unwrap(), network };
+        }
+
+        assert!("mainnet".parse::<Network>().is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::bolt_1::tlv::TlvError;
+
+/// Reads a [BigSize][0] integer, advancing the buffer past it.
+///
+/// [0]: https://github.com/lightning/bolts/blob/master/01-messaging.md#appendix-a-bigsize-test-vectors
+pub fn read_big_size(buf: &mut &[u8]) -> Result<u64, TlvError> {
+    let (&prefix, rest) = buf
+        .split_first()
+        .ok_or(TlvError::UnexpectedEnd { want: 1, got: 0 })?;
+
+    // The prefix defines how many bytes follow it, and the
+    // smallest value that requires such an encoding.
+    let (len, min) = match prefix {
+        0xff => (8, 0x1_0000_0000),
+        0xfe => (4, 0x1_0000),
+        0xfd => (2, 0xfd),
+        x => {
+            *buf = rest;
+            return Ok(x as u64);
+        }
+    };
+
+    if rest.len() < len {
+        return Err(TlvError::UnexpectedEnd {
+            want: len,
+            got: rest.len(),
+        });
+    }
+
+    let (x, rest) = rest.split_at(len);
+
+    let mut be = [0u8; 8];
+    be[8 - len..].copy_from_slice(x);
+    let x = u64::from_be_bytes(be);
+
+    if x < min {
+        return Err(TlvError::NonCanonicalBigSize);
+    }
+
+    *buf = rest;
+
+    Ok(x)
+}
+
+/// Writes a [BigSize][0] integer using the shortest encoding.
+///
+/// [0]: https://github.com/lightning/bolts/blob/master/01-messaging.md#appendix-a-bigsize-test-vectors
+pub fn write_big_size(buf: &mut Vec<u8>, x: u64) {
+    match x {
+        0..=0xfc => buf.push(x as u8),
+        0xfd..=0xffff => {
+            buf.push(0xfd);
+            buf.extend_from_slice(&(x as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            buf.push(0xfe);
+            buf.extend_from_slice(&(x as u32).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xff);
+            buf.extend_from_slice(&x.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // Values used for testing are taken from BOLT-1 test vectors.
+    // See: https://github.com/lightning/bolts/blob/master/01-messaging.md#appendix-a-bigsize-test-vectors
+    const VECTORS: [(u64, &[u8]); 8] = [
+        (0, &hex!("00")),
+        (252, &hex!("fc")),
+        (253, &hex!("fd00fd")),
+        (65535, &hex!("fdffff")),
+        (65536, &hex!("fe00010000")),
+        (4294967295, &hex!("feffffffff")),
+        (4294967296, &hex!("ff0000000100000000")),
+        (18446744073709551615, &hex!("ffffffffffffffffff")),
+    ];
+
+    #[test]
+    fn it_decodes_the_test_vectors() {
+        for (value, bytes) in VECTORS {
+            let mut buf = bytes;
+
+            assert_eq! { read_big_size(&mut buf).unwrap(), value };
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn it_encodes_the_test_vectors() {
+        for (value, bytes) in VECTORS {
+            let mut buf = Vec::new();
+
+            write_big_size(&mut buf, value);
+
+            assert_eq! { buf, bytes };
+        }
+    }
+
+    #[test]
+    fn it_rejects_non_canonical_encodings() {
+        for bytes in [
+            &hex!("fd00fc")[..],
+            &hex!("fe0000ffff"),
+            &hex!("ff00000000ffffffff"),
+        ] {
+            assert!(matches!(
+                read_big_size(&mut &bytes[..]),
+                Err(TlvError::NonCanonicalBigSize)
+            ));
+        }
+    }
+
+    #[test]
+    fn it_rejects_short_reads() {
+        for bytes in [
+            &hex!("fd00")[..],
+            &hex!("feffff"),
+            &hex!("ffffffffff"),
+            &hex!(""),
+            &hex!("fd"),
+            &hex!("fe"),
+            &hex!("ff"),
+        ] {
+            assert!(matches!(
+                read_big_size(&mut &bytes[..]),
+                Err(TlvError::UnexpectedEnd { .. })
+            ));
+        }
+    }
+}
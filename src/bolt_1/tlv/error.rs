@@ -0,0 +1,17 @@
+#[derive(Debug, thiserror::Error)]
+pub enum TlvError {
+    #[error("Unexpected end of TLV stream: want {want} bytes, got {got}")]
+    UnexpectedEnd { want: usize, got: usize },
+
+    #[error("The decoded BigSize is not canonical")]
+    NonCanonicalBigSize,
+
+    #[error("The TLV record of type '{current}' is not ordered after type '{previous}'")]
+    InvalidOrdering { previous: u64, current: u64 },
+
+    #[error("The TLV record of type '{0}' is duplicated")]
+    DuplicateRecord(u64),
+
+    #[error("The TLV record of type '{0}' is unknown and even")]
+    UnknownEvenRecord(u64),
+}
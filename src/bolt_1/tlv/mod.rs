@@ -0,0 +1,337 @@
+//! This module defines the Type-Length-Value format of the BOLT-1 protocol.
+//!
+//! A TLV stream is a sequence of records ordered by their strictly increasing type,
+//! where both the type and the length are encoded as BigSize integers.
+
+mod big_size;
+mod error;
+
+pub use self::big_size::{read_big_size, write_big_size};
+pub use self::error::TlvError;
+
+/// A single TLV record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlvRecord {
+    /// The type of the record.
+    pub record_type: u64,
+
+    /// The raw value of the record.
+    pub value: Vec<u8>,
+}
+
+/// A sequence of TLV records ordered by type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlvStream {
+    records: Vec<TlvRecord>,
+}
+
+impl TlvStream {
+    /// Decodes a TLV stream from the bytes passed.
+    ///
+    /// Records of unknown odd types are kept, while records of unknown even types
+    /// fail the decoding, as stated by the "it's OK to be odd" rule.
+    pub fn decode(mut bytes: &[u8], known_types: &[u64]) -> Result<Self, TlvError> {
+        let mut records: Vec<TlvRecord> = Vec::new();
+
+        while !bytes.is_empty() {
+            let record_type = read_big_size(&mut bytes)?;
+            let len = read_big_size(&mut bytes)?;
+
+            if (bytes.len() as u64) < len {
+                return Err(TlvError::UnexpectedEnd {
+                    want: len.try_into().unwrap_or(usize::MAX),
+                    got: bytes.len(),
+                });
+            }
+
+            let (value, rest) = bytes.split_at(len as usize);
+            bytes = rest;
+
+            if let Some(previous) = records.last() {
+                if record_type == previous.record_type {
+                    return Err(TlvError::DuplicateRecord(record_type));
+                }
+
+                if record_type < previous.record_type {
+                    return Err(TlvError::InvalidOrdering {
+                        previous: previous.record_type,
+                        current: record_type,
+                    });
+                }
+            }
+
+            if record_type % 2 == 0 && !known_types.contains(&record_type) {
+                return Err(TlvError::UnknownEvenRecord(record_type));
+            }
+
+            records.push(TlvRecord {
+                record_type,
+                value: value.to_vec(),
+            });
+        }
+
+        Ok(Self { records })
+    }
+
+    /// Returns the value of the record of the type passed.
+    pub fn get(&self, record_type: u64) -> Option<&[u8]> {
+        self.records
+            .iter()
+            .find(|x| x.record_type == record_type)
+            .map(|x| x.value.as_slice())
+    }
+
+    /// Inserts a record, keeping the stream ordered by type.
+    ///
+    /// Replaces the value of a record of the same type, if there is one.
+    pub fn insert(&mut self, record_type: u64, value: Vec<u8>) {
+        match self
+            .records
+            .binary_search_by_key(&record_type, |x| x.record_type)
+        {
+            Ok(i) => self.records[i].value = value,
+            Err(i) => self.records.insert(i, TlvRecord { record_type, value }),
+        }
+    }
+
+    /// Encodes the TLV stream into bytes.
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        for record in &self.records {
+            write_big_size(buf, record.record_type);
+            write_big_size(buf, record.value.len() as u64);
+            buf.extend_from_slice(&record.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use secp256k1::PublicKey;
+
+    // Values used for testing are taken from BOLT-1 test vectors.
+    // See: https://github.com/lightning/bolts/blob/master/01-messaging.md#appendix-b-type-length-value-test-vectors
+
+    /// The records of the `n1` namespace defined by the test vectors.
+    #[derive(Debug, PartialEq, Eq)]
+    enum N1 {
+        Tlv1 {
+            amount_msat: u64,
+        },
+        Tlv2 {
+            scid: u64,
+        },
+        Tlv3 {
+            node_id: PublicKey,
+            amount_msat_1: u64,
+            amount_msat_2: u64,
+        },
+        Tlv4 {
+            cltv_delta: u16,
+        },
+        Unknown(u64),
+    }
+
+    // Reads a truncated integer of at most `max` bytes without leading zeros.
+    fn read_tu64(value: &[u8], max: usize) -> Result<u64, String> {
+        if value.len() > max {
+            return Err("greater than encoding length".to_owned());
+        }
+
+        if value.first() == Some(&0) {
+            return Err("not minimally encoded".to_owned());
+        }
+
+        Ok(value.iter().fold(0, |x, &b| (x << 8) | b as u64))
+    }
+
+    fn decode_n1(bytes: &[u8]) -> Result<Vec<N1>, String> {
+        let stream = TlvStream::decode(bytes, &[1, 2, 3, 254]).map_err(|e| e.to_string())?;
+
+        stream
+            .records
+            .iter()
+            .map(
+                |TlvRecord { record_type, value }| match (record_type, value.len()) {
+                    (1, _) => Ok(N1::Tlv1 {
+                        amount_msat: read_tu64(value, 8)?,
+                    }),
+                    (2, 8) => Ok(N1::Tlv2 {
+                        scid: u64::from_be_bytes(value[..].try_into().unwrap()),
+                    }),
+                    (3, 49) => Ok(N1::Tlv3 {
+                        node_id: PublicKey::from_slice(&value[..33]).map_err(|e| e.to_string())?,
+                        amount_msat_1: u64::from_be_bytes(value[33..41].try_into().unwrap()),
+                        amount_msat_2: u64::from_be_bytes(value[41..].try_into().unwrap()),
+                    }),
+                    (254, 2) => Ok(N1::Tlv4 {
+                        cltv_delta: u16::from_be_bytes(value[..].try_into().unwrap()),
+                    }),
+                    (2 | 3 | 254, _) => Err("invalid encoding length".to_owned()),
+                    (x, _) => Ok(N1::Unknown(*x)),
+                },
+            )
+            .collect()
+    }
+
+    fn decode_n2(bytes: &[u8]) -> Result<TlvStream, TlvError> {
+        TlvStream::decode(bytes, &[0, 11])
+    }
+
+    #[test]
+    fn it_fails_to_decode_invalid_streams_in_any_namespace() {
+        let mut truncated = hex!("0ffd0201").to_vec();
+        truncated.extend_from_slice(&[0; 512]);
+
+        for bytes in [
+            &hex!("fd")[..],
+            &hex!("fd01"),
+            &hex!("fd000100"),
+            &hex!("fd0101"),
+            &hex!("0ffd"),
+            &hex!("0ffd26"),
+            &hex!("0ffd2602"),
+            &hex!("0ffd000100"),
+            &truncated,
+            &hex!("1200"),
+            &hex!("fd010200"),
+            &hex!("fe0100000200"),
+            &hex!("ff010000000000000200"),
+        ] {
+            assert!(decode_n1(bytes).is_err(), "{}", hex::encode(bytes));
+            assert!(decode_n2(bytes).is_err(), "{}", hex::encode(bytes));
+        }
+    }
+
+    #[test]
+    fn it_fails_to_decode_invalid_streams_in_the_n1_namespace() {
+        for bytes in [
+            &hex!("0109ffffffffffffffffff")[..],
+            &hex!("010100"),
+            &hex!("01020001"),
+            &hex!("0103000100"),
+            &hex!("010400010000"),
+            &hex!("01050001000000"),
+            &hex!("0106000100000000"),
+            &hex!("010700010000000000"),
+            &hex!("01080001000000000000"),
+            &hex!("020701010101010101"),
+            &hex!("0209010101010101010101"),
+            &hex!("0321023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb"),
+            &hex!("0329023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb0000000000000001"),
+            &hex!("0330023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb000000000000000100000000000001"),
+            &hex!("0331043da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb00000000000000010000000000000002"),
+            &hex!("0332023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb0000000000000001000000000000000001"),
+            &hex!("fd00fe00"),
+            &hex!("fd00fe0101"),
+            &hex!("fd00fe03010101"),
+            &hex!("0000"),
+        ] {
+            assert!(decode_n1(bytes).is_err(), "{}", hex::encode(bytes));
+        }
+    }
+
+    #[test]
+    fn it_decodes_valid_streams_in_any_namespace() {
+        for bytes in [
+            &hex!("")[..],
+            &hex!("2100"),
+            &hex!("fd020100"),
+            &hex!("fd00fd00"),
+            &hex!("fd00ff00"),
+            &hex!("fe0200000100"),
+            &hex!("ff020000000000000100"),
+        ] {
+            assert!(decode_n1(bytes).is_ok(), "{}", hex::encode(bytes));
+
+            let stream = decode_n2(bytes).unwrap();
+
+            let mut buf = Vec::new();
+            stream.encode(&mut buf);
+
+            assert_eq! { buf, bytes };
+        }
+    }
+
+    #[test]
+    fn it_decodes_valid_streams_in_the_n1_namespace() {
+        let node_id = hex!("023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb");
+        let node_id = PublicKey::from_slice(&node_id).unwrap();
+
+        for (bytes, record) in [
+            (&hex!("0100")[..], N1::Tlv1 { amount_msat: 0 }),
+            (&hex!("010101"), N1::Tlv1 { amount_msat: 1 }),
+            (&hex!("01020100"), N1::Tlv1 { amount_msat: 256 }),
+            (&hex!("0103010000"), N1::Tlv1 { amount_msat: 65536 }),
+            (&hex!("010401000000"), N1::Tlv1 { amount_msat: 16777216 }),
+            (&hex!("01050100000000"), N1::Tlv1 { amount_msat: 4294967296 }),
+            (&hex!("0106010000000000"), N1::Tlv1 { amount_msat: 1099511627776 }),
+            (&hex!("010701000000000000"), N1::Tlv1 { amount_msat: 281474976710656 }),
+            (&hex!("01080100000000000000"), N1::Tlv1 { amount_msat: 72057594037927936 }),
+            (&hex!("02080000000000000226"), N1::Tlv2 { scid: 550 }),
+            (
+                &hex!("0331023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb00000000000000010000000000000002"),
+                N1::Tlv3 { node_id, amount_msat_1: 1, amount_msat_2: 2 },
+            ),
+            (&hex!("fd00fe020226"), N1::Tlv4 { cltv_delta: 550 }),
+        ] {
+            assert_eq! { decode_n1(bytes).unwrap(), vec![record], "{}", hex::encode(bytes) };
+        }
+    }
+
+    #[test]
+    fn it_inserts_records_in_order() {
+        let mut stream = TlvStream::default();
+
+        stream.insert(3, vec![0x03]);
+        stream.insert(1, vec![0x01]);
+        stream.insert(3, vec![0x33]);
+
+        assert_eq! { stream.get(1), Some(&[0x01][..]) };
+        assert_eq! { stream.get(2), None };
+        assert_eq! { stream.get(3), Some(&[0x33][..]) };
+
+        let mut buf = Vec::new();
+        stream.encode(&mut buf);
+
+        assert_eq! { buf, hex!("010101030133") };
+    }
+
+    #[test]
+    fn it_fails_to_decode_streams_with_invalid_ordering() {
+        assert!(matches!(
+            TlvStream::decode(&hex!("0208000000000000022601012a"), &[1, 2]),
+            Err(TlvError::InvalidOrdering {
+                previous: 2,
+                current: 1
+            })
+        ));
+        assert!(matches!(
+            TlvStream::decode(&hex!("1f000f012a"), &[]),
+            Err(TlvError::InvalidOrdering {
+                previous: 31,
+                current: 15
+            })
+        ));
+        assert!(matches!(
+            decode_n2(&hex!("ffffffffffffffffff000000")),
+            Err(TlvError::InvalidOrdering {
+                previous: u64::MAX,
+                current: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn it_fails_to_decode_streams_with_duplicated_records() {
+        assert!(matches!(
+            TlvStream::decode(&hex!("0208000000000000023102080000000000000451"), &[1, 2]),
+            Err(TlvError::DuplicateRecord(2))
+        ));
+        assert!(matches!(
+            TlvStream::decode(&hex!("1f001f012a"), &[]),
+            Err(TlvError::DuplicateRecord(31))
+        ));
+    }
+}
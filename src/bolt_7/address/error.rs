@@ -0,0 +1,25 @@
+#[derive(Debug, thiserror::Error)]
+pub enum AddressError {
+    #[error(
+        "The '{0}' is not a valid node address. Expected format: <public_key>@<host>[:<port>]"
+    )]
+    InvalidNodeAddress(String),
+
+    #[error("The '{hex}' is not a valid public key")]
+    InvalidPublicKey { hex: String, source: eyre::Report },
+
+    #[error("The '{0}' is not a valid host")]
+    InvalidHost(String),
+
+    #[error("The '{0}' is not a valid Tor v3 onion address")]
+    InvalidOnionAddress(String),
+
+    #[error("The '{0}' is not a valid port")]
+    InvalidPort(String),
+
+    #[error("Unexpected end of address descriptor: want {want} bytes, got {got}")]
+    UnexpectedEnd { want: usize, got: usize },
+
+    #[error("The address descriptor of type '{0}' is not supported")]
+    UnsupportedType(u8),
+}
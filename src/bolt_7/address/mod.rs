@@ -0,0 +1,407 @@
+//! This module defines the network addresses of the nodes.
+//!
+//! An address is encoded as a descriptor, which consists of a 1-byte type
+//! followed by the host and the 2-byte big-endian port.
+
+mod error;
+mod node_address;
+
+pub use self::error::AddressError;
+pub use self::node_address::NodeAddress;
+
+#[cfg(feature = "async")]
+use crate::socks5::{Socks5Error, Socks5Proxy};
+use data_encoding::BASE32_NOPAD;
+use sha3::{Digest, Sha3_256};
+use std::{
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+#[cfg(feature = "async")]
+use tokio::net::TcpStream;
+
+/// The port a node listens on when none is specified.
+pub const DEFAULT_PORT: u16 = 9735;
+
+/// The version of the Tor onion services supported.
+const ONION_VERSION: u8 = 3;
+
+/// The host of a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Host {
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+
+    /// A Tor v3 onion service, made of its public key, checksum and version.
+    TorV3([u8; 35]),
+
+    /// A DNS hostname, which only contains ASCII characters.
+    Hostname(String),
+}
+
+impl Host {
+    // Returns the type of the address descriptor of the host.
+    fn descriptor_type(&self) -> u8 {
+        match self {
+            Self::Ipv4(_) => 1,
+            Self::Ipv6(_) => 2,
+            Self::TorV3(_) => 4,
+            Self::Hostname(_) => 5,
+        }
+    }
+}
+
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ipv4(ip) => write!(f, "{ip}"),
+            Self::Ipv6(ip) => write!(f, "[{ip}]"),
+            Self::TorV3(onion) => {
+                write!(f, "{}.onion", BASE32_NOPAD.encode(onion).to_lowercase())
+            }
+            Self::Hostname(hostname) => write!(f, "{hostname}"),
+        }
+    }
+}
+
+impl FromStr for Host {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(ip) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            return ip
+                .parse()
+                .map(Self::Ipv6)
+                .map_err(|_| AddressError::InvalidHost(s.to_owned()));
+        }
+
+        if let Ok(ip) = s.parse() {
+            return Ok(Self::Ipv4(ip));
+        }
+
+        if let Some(onion) = s.strip_suffix(".onion") {
+            return decode_onion(onion)
+                .map(Self::TorV3)
+                .ok_or_else(|| AddressError::InvalidOnionAddress(s.to_owned()));
+        }
+
+        if !is_valid_hostname(s) {
+            return Err(AddressError::InvalidHost(s.to_owned()));
+        }
+
+        Ok(Self::Hostname(s.to_owned()))
+    }
+}
+
+/// The network address of a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    pub host: Host,
+    pub port: u16,
+}
+
+impl Address {
+    /// Encodes the address as a descriptor.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![self.host.descriptor_type()];
+
+        match &self.host {
+            Host::Ipv4(ip) => buf.extend_from_slice(&ip.octets()),
+            Host::Ipv6(ip) => buf.extend_from_slice(&ip.octets()),
+            Host::TorV3(onion) => buf.extend_from_slice(onion),
+            Host::Hostname(hostname) => {
+                buf.push(hostname.len() as u8);
+                buf.extend_from_slice(hostname.as_bytes());
+            }
+        }
+
+        buf.extend_from_slice(&self.port.to_be_bytes());
+
+        buf
+    }
+
+    /// Decodes the address descriptor at the start of the bytes,
+    /// and returns the remaining bytes.
+    pub fn decode(bytes: &[u8]) -> Result<(Self, &[u8]), AddressError> {
+        let (descriptor_type, bytes) = split(bytes, 1)?;
+
+        let (host, bytes) = match descriptor_type[0] {
+            1 => {
+                let (ip, bytes) = split(bytes, 4)?;
+                let ip: [u8; 4] = ip.try_into().unwrap();
+
+                (Host::Ipv4(ip.into()), bytes)
+            }
+            2 => {
+                let (ip, bytes) = split(bytes, 16)?;
+                let ip: [u8; 16] = ip.try_into().unwrap();
+
+                (Host::Ipv6(ip.into()), bytes)
+            }
+            4 => {
+                let (onion, bytes) = split(bytes, 35)?;
+                let onion: [u8; 35] = onion.try_into().unwrap();
+
+                if !is_valid_onion(&onion) {
+                    return Err(AddressError::InvalidOnionAddress(
+                        Host::TorV3(onion).to_string(),
+                    ));
+                }
+
+                (Host::TorV3(onion), bytes)
+            }
+            5 => {
+                let (len, bytes) = split(bytes, 1)?;
+                let (hostname, bytes) = split(bytes, len[0] as usize)?;
+
+                let hostname = String::from_utf8_lossy(hostname);
+
+                if !is_valid_hostname(&hostname) {
+                    return Err(AddressError::InvalidHost(hostname.into_owned()));
+                }
+
+                (Host::Hostname(hostname.into_owned()), bytes)
+            }
+            x => return Err(AddressError::UnsupportedType(x)),
+        };
+
+        let (port, bytes) = split(bytes, 2)?;
+        let port = u16::from_be_bytes([port[0], port[1]]);
+
+        Ok((Self { host, port }, bytes))
+    }
+
+    /// Opens a TCP connection to the address, either through the proxy or directly,
+    /// resolving the hostname if needed.
+    ///
+    /// A Tor onion service can only be reached through a proxy.
+    #[cfg(feature = "async")]
+    pub async fn connect(&self, proxy: Option<&Socks5Proxy>) -> Result<TcpStream, Socks5Error> {
+        if let Some(proxy) = proxy {
+            return proxy.connect(self).await;
+        }
+
+        let stream = match &self.host {
+            Host::Ipv4(ip) => TcpStream::connect((*ip, self.port)).await,
+            Host::Ipv6(ip) => TcpStream::connect((*ip, self.port)).await,
+            Host::Hostname(hostname) => TcpStream::connect((hostname.as_str(), self.port)).await,
+            Host::TorV3(_) => return Err(Socks5Error::ProxyRequired),
+        }?;
+
+        Ok(stream)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The port follows the closing bracket of an IPv6 address, or the only colon otherwise.
+        let (host, port) = match s.rfind(':') {
+            Some(i) if s[i..].contains(']') || (!s.starts_with('[') && s[..i].contains(':')) => {
+                (s, None)
+            }
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| AddressError::InvalidPort(port.to_owned()))?,
+            None => DEFAULT_PORT,
+        };
+
+        Ok(Self {
+            host: host.parse()?,
+            port,
+        })
+    }
+}
+
+// Splits the bytes at `n`, failing when there are fewer bytes.
+fn split(bytes: &[u8], n: usize) -> Result<(&[u8], &[u8]), AddressError> {
+    if bytes.len() < n {
+        return Err(AddressError::UnexpectedEnd {
+            want: n,
+            got: bytes.len(),
+        });
+    }
+
+    Ok(bytes.split_at(n))
+}
+
+// Returns whether the hostname only contains letters, digits, hyphens and dots,
+// and fits in the 1-byte length of its descriptor.
+fn is_valid_hostname(hostname: &str) -> bool {
+    !hostname.is_empty()
+        && hostname.len() <= u8::MAX as usize
+        && hostname
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+}
+
+// Decodes the base32 part of an onion address:
+//     - 32 bytes for the public key of the service;
+//     - 2 bytes for the checksum;
+//     - 1 byte for the version;
+fn decode_onion(onion: &str) -> Option<[u8; 35]> {
+    let bytes = BASE32_NOPAD.decode(onion.to_uppercase().as_bytes()).ok()?;
+    let onion = bytes.try_into().ok()?;
+
+    is_valid_onion(&onion).then_some(onion)
+}
+
+// Returns whether the onion address has the supported version and a valid checksum,
+// which is the first 2 bytes of SHA3-256(".onion checksum" || public key || version).
+fn is_valid_onion(onion: &[u8; 35]) -> bool {
+    let (public_key, rest) = onion.split_at(32);
+    let (checksum, version) = rest.split_at(2);
+
+    let hash = Sha3_256::new()
+        .chain_update(b".onion checksum")
+        .chain_update(public_key)
+        .chain_update(version)
+        .finalize();
+
+    version[0] == ONION_VERSION && checksum == &hash[..2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const ONION: &str = "duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzczad.onion";
+
+    #[test]
+    fn it_parses_and_displays_the_addresses() {
+        for (s, host, port) in [
+            ("127.0.0.1:9736", Host::Ipv4(Ipv4Addr::LOCALHOST), 9736),
+            ("[::1]:9736", Host::Ipv6(Ipv6Addr::LOCALHOST), 9736),
+            (
+                "node.example.com:9736",
+                Host::Hostname("node.example.com".to_owned()),
+                9736,
+            ),
+        ] {
+            let address = s.parse::<Address>().unwrap();
+
+            assert_eq! { address, Address { host, port } };
+            assert_eq! { address.to_string(), s };
+        }
+
+        let address = format!("{ONION}:9736").parse::<Address>().unwrap();
+        assert!(matches!(address.host, Host::TorV3(_)));
+        assert_eq! { address.to_string(), format!("{ONION}:9736") };
+    }
+
+    #[test]
+    fn it_defaults_to_the_lightning_port() {
+        for s in ["127.0.0.1", "[::1]", "localhost", ONION] {
+            assert_eq! { s.parse::<Address>().unwrap().port, DEFAULT_PORT };
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_addresses() {
+        assert!(matches!(
+            "::1".parse::<Address>(),
+            Err(AddressError::InvalidHost(_))
+        ));
+        assert!(matches!(
+            "[::1]]:9735".parse::<Address>(),
+            Err(AddressError::InvalidHost(_))
+        ));
+        assert!(matches!(
+            "localhost:port".parse::<Address>(),
+            Err(AddressError::InvalidPort(_))
+        ));
+        assert!(matches!(
+            "localhost:65536".parse::<Address>(),
+            Err(AddressError::InvalidPort(_))
+        ));
+        assert!(matches!(
+            "local_host".parse::<Address>(),
+            Err(AddressError::InvalidHost(_))
+        ));
+
+        // The last character of the onion address is altered, which breaks the checksum.
+        let onion = ONION.replace("czad.onion", "czae.onion");
+        assert!(matches!(
+            onion.parse::<Address>(),
+            Err(AddressError::InvalidOnionAddress(_))
+        ));
+    }
+
+    #[test]
+    fn it_encodes_and_decodes_the_descriptors() {
+        let onion = ONION.parse::<Host>().unwrap();
+        let Host::TorV3(onion_bytes) = onion else {
+            panic!("Expected a Tor v3 onion address");
+        };
+
+        for (address, descriptor) in [
+            (
+                "127.0.0.1:9735",
+                [&hex!("01 7f000001")[..], &hex!("2607")].concat(),
+            ),
+            (
+                "[::1]:9735",
+                [
+                    &hex!("02 00000000000000000000000000000001")[..],
+                    &hex!("2607"),
+                ]
+                .concat(),
+            ),
+            (
+                &format!("{ONION}:9735"),
+                [&hex!("04")[..], &onion_bytes, &hex!("2607")].concat(),
+            ),
+            (
+                "localhost:9735",
+                [&hex!("05 09")[..], b"localhost", &hex!("2607")].concat(),
+            ),
+        ] {
+            let address = address.parse::<Address>().unwrap();
+
+            assert_eq! { address.encode(), descriptor };
+
+            let mut bytes = descriptor.clone();
+            bytes.extend_from_slice(&[0xff; 3]);
+
+            let (decoded, rest) = Address::decode(&bytes).unwrap();
+            assert_eq! { decoded, address };
+            assert_eq! { rest, [0xff; 3] };
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_descriptors() {
+        assert!(matches!(
+            Address::decode(&hex!("01 7f0000")),
+            Err(AddressError::UnexpectedEnd { want: 4, got: 3 })
+        ));
+        assert!(matches!(
+            Address::decode(&hex!("01 7f000001 26")),
+            Err(AddressError::UnexpectedEnd { want: 2, got: 1 })
+        ));
+        assert!(matches!(
+            Address::decode(&hex!("05 03 2f2f2f 2607")),
+            Err(AddressError::InvalidHost(_))
+        ));
+
+        // Tor v2 onion addresses are deprecated.
+        assert!(matches!(
+            Address::decode(&[&[3][..], &[0; 12]].concat()),
+            Err(AddressError::UnsupportedType(3))
+        ));
+    }
+}
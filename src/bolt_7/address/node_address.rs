@@ -0,0 +1,93 @@
+use crate::bolt_7::address::{Address, AddressError};
+use secp256k1::PublicKey;
+use std::{fmt, str::FromStr};
+
+/// The address of a remote node in the following form: `<public_key>@<host>[:<port>]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeAddress {
+    /// The static public key of the node.
+    pub public_key: PublicKey,
+
+    /// The network address of the node.
+    pub address: Address,
+}
+
+impl fmt::Display for NodeAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}@{}",
+            hex::encode(self.public_key.serialize()),
+            self.address
+        )
+    }
+}
+
+impl FromStr for NodeAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (public_key, address) = s
+            .split_once('@')
+            .ok_or_else(|| AddressError::InvalidNodeAddress(s.to_owned()))?;
+
+        let public_key = hex::decode(public_key)
+            .map_err(eyre::Report::new)
+            .and_then(|bytes| PublicKey::from_slice(&bytes).map_err(eyre::Report::new))
+            .map_err(|e| AddressError::InvalidPublicKey {
+                hex: public_key.to_owned(),
+                source: e,
+            })?;
+
+        Ok(Self {
+            public_key,
+            address: address.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_7::address::{Host, DEFAULT_PORT};
+
+    const PUBLIC_KEY: &str = "028d7500dd4c12685d1f568b4c2b5048e8534b873319f3a8daa612b469132ec7f7";
+
+    #[test]
+    fn it_parses_and_displays_the_node_address() {
+        let s = format!("{PUBLIC_KEY}@[2001:db8::1]:9736");
+        let node_address = s.parse::<NodeAddress>().unwrap();
+
+        assert_eq! { hex::encode(node_address.public_key.serialize()), PUBLIC_KEY };
+        assert_eq! { node_address.address.host, Host::Ipv6("2001:db8::1".parse().unwrap()) };
+        assert_eq! { node_address.address.port, 9736 };
+        assert_eq! { node_address.to_string(), s };
+
+        let node_address = format!("{PUBLIC_KEY}@localhost")
+            .parse::<NodeAddress>()
+            .unwrap();
+
+        assert_eq! { node_address.address.port, DEFAULT_PORT };
+        assert_eq! { node_address.to_string(), format!("{PUBLIC_KEY}@localhost:9735") };
+    }
+
+    #[test]
+    fn it_rejects_invalid_node_addresses() {
+        assert!(matches!(
+            "127.0.0.1:9735".parse::<NodeAddress>(),
+            Err(AddressError::InvalidNodeAddress(_))
+        ));
+        assert!(matches!(
+            "02aa@127.0.0.1:9735".parse::<NodeAddress>(),
+            Err(AddressError::InvalidPublicKey { .. })
+        ));
+        assert!(matches!(
+            "not-hex@127.0.0.1:9735".parse::<NodeAddress>(),
+            Err(AddressError::InvalidPublicKey { .. })
+        ));
+        assert!(matches!(
+            format!("{PUBLIC_KEY}@").parse::<NodeAddress>(),
+            Err(AddressError::InvalidHost(_))
+        ));
+    }
+}
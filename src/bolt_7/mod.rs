@@ -0,0 +1,5 @@
+//! This module is an implementation of the BOLT-7 protocol.
+//!
+//! Spec: <https://github.com/lightning/bolts/blob/master/07-routing-gossip.md>
+
+pub mod address;
@@ -0,0 +1,110 @@
+use crate::bolt_8::crypto::CryptoError;
+use chacha20poly1305::{aead::AeadInPlace, ChaCha20Poly1305};
+use digest::{generic_array::GenericArray, KeyInit};
+use std::fmt;
+
+/// A ChaCha20-Poly1305 (IETF variant) cipher bound to a key,
+/// which encrypts and decrypts in place with a detached tag.
+///
+/// The key schedule is computed once, so the cipher should be kept until the key changes.
+pub struct Cipher(ChaCha20Poly1305);
+
+impl Cipher {
+    /// Creates a cipher for the key passed.
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self(ChaCha20Poly1305::new(key.into()))
+    }
+
+    /// Encrypts the buffer in place and returns its tag.
+    pub fn encrypt_in_place(
+        &self,
+        n: u64,
+        ad: &[u8],
+        buf: &mut [u8],
+    ) -> Result<[u8; 16], CryptoError> {
+        let tag = self
+            .0
+            .encrypt_in_place_detached(&nonce(n), ad, buf)
+            .map_err(|e| CryptoError::EncryptionFailed {
+                source: eyre::eyre!(e),
+            })?;
+
+        Ok(tag.into())
+    }
+
+    /// Decrypts the buffer in place, after checking its tag.
+    ///
+    /// The buffer is left unchanged when the tag is not valid.
+    pub fn decrypt_in_place(
+        &self,
+        n: u64,
+        ad: &[u8],
+        buf: &mut [u8],
+        tag: &[u8; 16],
+    ) -> Result<(), CryptoError> {
+        self.0
+            .decrypt_in_place_detached(&nonce(n), ad, buf, tag.into())
+            .map_err(|e| CryptoError::DecryptionFailed {
+                source: eyre::eyre!(e),
+            })
+    }
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Cipher([REDACTED])")
+    }
+}
+
+// Returns the nonce of the Noise Protocol convention,
+// 32 zero bits, followed by a little-endian 64-bit value.
+fn nonce(n: u64) -> GenericArray<u8, chacha20poly1305::consts::U12> {
+    let mut nonce = [0; 12];
+    nonce[4..].copy_from_slice(&n.to_le_bytes());
+
+    nonce.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::crypto::{decrypt_with_ad, encrypt_with_ad};
+    use hex_literal::hex;
+
+    #[test]
+    fn it_matches_the_allocating_functions() {
+        let key = hex!("e68f69b7f096d7917245f5e5cf8ae1595febe4d4644333c99f9c4a1282031c9f");
+        let ad = hex!("9e0e7de8bb75554f21db034633de04be41a2b8a18da7a319a03c803bf02b396c");
+        let cipher = Cipher::new(&key);
+
+        for n in [0, 1, 999] {
+            let mut buf = *b"hello";
+            let tag = cipher.encrypt_in_place(n, &ad, &mut buf).unwrap();
+
+            let c = encrypt_with_ad(&key, n, &ad, b"hello").unwrap();
+            assert_eq! { [&buf[..], &tag].concat(), c };
+
+            assert_eq! { decrypt_with_ad(&key, n, &ad, &c).unwrap(), b"hello" };
+
+            cipher.decrypt_in_place(n, &ad, &mut buf, &tag).unwrap();
+            assert_eq! { buf, *b"hello" };
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_bad_tag() {
+        let cipher = Cipher::new(&[0x42; 32]);
+
+        let mut buf = *b"hello";
+        let mut tag = cipher.encrypt_in_place(0, &[], &mut buf).unwrap();
+        let c = buf;
+
+        tag[0] ^= 1;
+
+        assert!(matches!(
+            cipher.decrypt_in_place(0, &[], &mut buf, &tag),
+            Err(CryptoError::DecryptionFailed { .. })
+        ));
+        assert_eq! { buf, c };
+    }
+}
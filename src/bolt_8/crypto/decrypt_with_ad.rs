@@ -3,7 +3,6 @@ use chacha20poly1305::{
     aead::{Aead, Payload},
     ChaCha20Poly1305,
 };
-use color_eyre::eyre;
 use digest::{generic_array::GenericArray, KeyInit};
 
 /// Performs a ChaCha20-Poly1305 (IETF variant) decryption on the arguments passed.
@@ -1,10 +1,13 @@
+use crate::bolt_8::crypto::Secret;
 use secp256k1::{ecdh::SharedSecret, PublicKey, SecretKey};
 
 /// Performs an Elliptic-Curve Diffie-Hellman operation.
 ///
 /// Returns the SHA256 digest of the generated point.
-pub fn ecdh(pk: &PublicKey, sk: &SecretKey) -> [u8; 32] {
-    SharedSecret::new(pk, sk).secret_bytes()
+pub fn ecdh(pk: &PublicKey, sk: &SecretKey) -> Secret<[u8; 32]> {
+    let ss = Secret::new(SharedSecret::new(pk, sk));
+
+    Secret::new(ss.secret_bytes())
 }
 
 #[cfg(test)]
@@ -25,6 +28,6 @@ mod tests {
 
         let es = ecdh(&pk, &sk);
 
-        assert_eq! { es, hex!("1e2fb3c8fe8fb9f262f649f64d26ecf0f2c0a805a767cf02dc2d77a6ef1fdcc3") };
+        assert_eq! { *es, hex!("1e2fb3c8fe8fb9f262f649f64d26ecf0f2c0a805a767cf02dc2d77a6ef1fdcc3") };
     }
 }
@@ -1,5 +1,3 @@
-use color_eyre::eyre;
-
 #[derive(Debug, thiserror::Error)]
 pub enum CryptoError {
     #[error("Encryption failed")]
@@ -1,3 +1,4 @@
+use crate::bolt_8::crypto::Secret;
 use hkdf::Hkdf;
 use sha2::Sha256;
 
@@ -6,21 +7,27 @@ use sha2::Sha256;
 /// Returns the chaining key (ck) and the intermediate key (temp_k).
 ///
 /// [0]: https://datatracker.ietf.org/doc/html/rfc5869
-pub fn hkdf(salt: &[u8], ikm: &[u8]) -> ([u8; 32], [u8; 32]) {
+pub fn hkdf(salt: &[u8; 32], ikm: impl AsRef<[u8]>) -> (Secret<[u8; 32]>, Secret<[u8; 32]>) {
     // According to the specification, the evaluation
     // should be performed with a zero-length info field.
     let info = &[];
 
-    let hk = Hkdf::<Sha256>::new(Some(salt), ikm);
+    let hk = Hkdf::<Sha256>::new(Some(salt), ikm.as_ref());
 
     // According to the specification, the expand should return
     // 64 bytes of cryptographic randomness.
-    let mut buf = [0u8; 64];
-    hk.expand(info, &mut buf).unwrap();
+    //
+    // The buffer is wiped once the keys are copied out of it.
+    let mut buf = Secret::new([0u8; 64]);
+    hk.expand(info, &mut *buf).unwrap();
 
-    let (ck, temp_k) = buf.split_at(32);
+    let mut ck = Secret::new([0u8; 32]);
+    let mut temp_k = Secret::new([0u8; 32]);
 
-    (ck.try_into().unwrap(), temp_k.try_into().unwrap())
+    ck.copy_from_slice(&buf[..32]);
+    temp_k.copy_from_slice(&buf[32..]);
+
+    (ck, temp_k)
 }
 
 #[cfg(test)]
@@ -36,9 +43,9 @@ mod tests {
         let salt = hex!("2640f52eebcd9e882958951c794250eedb28002c05d7dc2ea0f195406042caf1");
         let ikm = hex!("1e2fb3c8fe8fb9f262f649f64d26ecf0f2c0a805a767cf02dc2d77a6ef1fdcc3");
 
-        let (ck, temp_k) = hkdf(&salt, &ikm);
+        let (ck, temp_k) = hkdf(&salt, ikm);
 
-        assert_eq! { ck, hex!("b61ec1191326fa240decc9564369dbb3ae2b34341d1e11ad64ed89f89180582f") };
-        assert_eq! { temp_k, hex!("e68f69b7f096d7917245f5e5cf8ae1595febe4d4644333c99f9c4a1282031c9f") };
+        assert_eq! { *ck, hex!("b61ec1191326fa240decc9564369dbb3ae2b34341d1e11ad64ed89f89180582f") };
+        assert_eq! { *temp_k, hex!("e68f69b7f096d7917245f5e5cf8ae1595febe4d4644333c99f9c4a1282031c9f") };
     }
 }
@@ -1,16 +1,22 @@
 //! This module contains all the cryptographic and hashing
 //! functionality required by the BOLT-8 protocol.
 
+mod cipher;
 mod decrypt_with_ad;
 mod ecdh;
 mod encrypt_with_ad;
 mod error;
 mod hkdf;
+mod secret;
 mod sha256_digest;
 
+pub use self::cipher::Cipher;
 pub use self::decrypt_with_ad::decrypt_with_ad;
 pub use self::ecdh::ecdh;
 pub use self::encrypt_with_ad::encrypt_with_ad;
 pub use self::error::CryptoError;
 pub use self::hkdf::hkdf;
+#[cfg(test)]
+pub(crate) use self::secret::read_after_drop;
+pub use self::secret::Secret;
 pub use self::sha256_digest::Sha256Digest;
@@ -0,0 +1,141 @@
+use secp256k1::{ecdh::SharedSecret, SecretKey};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// A wrapper for secret values, which are wiped from memory when dropped and redacted when printed.
+pub struct Secret<T: Erase>(T);
+
+/// A value that can be wiped from memory.
+pub trait Erase {
+    /// Overwrites the value in place.
+    fn erase(&mut self);
+}
+
+impl<T: Erase> Secret<T> {
+    /// Wraps the secret value.
+    pub fn new(x: T) -> Self {
+        Self(x)
+    }
+}
+
+impl<T: Erase> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Erase> DerefMut for Secret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Erase + AsRef<[u8]>> AsRef<[u8]> for Secret<T> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl<T: Erase> Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.0.erase();
+    }
+}
+
+impl<T: Erase> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<T: Erase> ZeroizeOnDrop for Secret<T> {}
+
+impl<T: Erase> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Erase + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<const N: usize> Erase for [u8; N] {
+    fn erase(&mut self) {
+        self.zeroize();
+    }
+}
+
+// The keys of secp256k1 do not implement `Zeroize`, but provide a best-effort erasure.
+impl Erase for SecretKey {
+    fn erase(&mut self) {
+        self.non_secure_erase();
+    }
+}
+
+impl Erase for SharedSecret {
+    fn erase(&mut self) {
+        self.non_secure_erase();
+    }
+}
+
+// Drops the value in place and returns the field as it was left in memory,
+// to check that the secrets are wiped rather than merely claimed to be.
+#[cfg(test)]
+pub(crate) fn read_after_drop<T, U: Copy>(value: T, field: impl FnOnce(&T) -> &U) -> U {
+    let mut value = std::mem::MaybeUninit::new(value);
+    let p = value.as_mut_ptr();
+
+    // SAFETY: the value is initialized until it is dropped, once, and the memory of the field
+    // stays allocated afterwards since `MaybeUninit` never drops its content. The field is `Copy`,
+    // so its bytes are still a valid value once the value around it is dropped.
+    unsafe {
+        let offset = field(&*p) as *const U as usize - p as usize;
+        std::ptr::drop_in_place(p);
+
+        p.cast::<u8>().add(offset).cast::<U>().read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_wipes_the_secret() {
+        let mut secret = Secret::new([0x42u8; 32]);
+        secret.zeroize();
+
+        assert_eq! { *secret, [0; 32] };
+
+        let mut sk = Secret::new(SecretKey::from_slice(&[0x42; 32]).unwrap());
+        sk.zeroize();
+
+        assert_ne! { sk.secret_bytes(), [0x42; 32] };
+    }
+
+    #[test]
+    fn it_wipes_the_secret_on_drop() {
+        let secret = Secret::new([0x42u8; 32]);
+
+        assert_eq! { read_after_drop(secret, |x| &**x), [0; 32] };
+
+        let sk = Secret::new(SecretKey::from_slice(&[0x42; 32]).unwrap());
+
+        assert_eq! { read_after_drop(sk, |x| &**x).secret_bytes(), [1; 32] };
+    }
+
+    #[test]
+    fn it_redacts_the_secret() {
+        let secret = Secret::new([0x42u8; 32]);
+
+        assert_eq! { format!("{secret:?}"), "Secret([REDACTED])" };
+    }
+}
@@ -1,6 +1,7 @@
 use sha2::{Digest, Sha256};
 
 /// A wrapper for SHA256 that accumulates hashes.
+#[derive(Debug)]
 pub struct Sha256Digest {
     digest: Option<[u8; 32]>,
 }
@@ -33,6 +34,12 @@ impl Sha256Digest {
     }
 }
 
+impl Default for Sha256Digest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,13 +1,14 @@
-use crate::bolt_8::crypto::Sha256Digest;
+use crate::bolt_8::crypto::{Secret, Sha256Digest};
 use secp256k1::PublicKey;
 
 /// Accumulates the state during the Act-0 of the handshake procedure.
+#[derive(Debug)]
 pub struct Act0 {
     /// The static public key of the remote node.
     pub(super) rs_pk: PublicKey,
 
     /// The chaining key.
-    pub(super) ck: [u8; 32],
+    pub(super) ck: Secret<[u8; 32]>,
 
     /// The handshake hash.
     pub(super) h: Sha256Digest,
@@ -15,12 +16,12 @@ pub struct Act0 {
 
 impl Act0 {
     /// Initiates the Act-0 of the handshake procedure.
-    pub fn new(rs_pk: PublicKey) -> Self {
+    pub(super) fn new(rs_pk: PublicKey) -> Self {
         let mut h = Sha256Digest::new();
 
         h.update(b"Noise_XK_secp256k1_ChaChaPoly_SHA256"); // Protocol name;
 
-        let ck = h.as_bytes().to_owned();
+        let ck = Secret::new(*h.as_bytes());
 
         h.update(b"lightning"); // Prologue;
 
@@ -46,7 +47,7 @@ mod tests {
         let act_0 = Act0::new(rs_pk);
 
         assert_eq! { act_0.rs_pk, rs_pk };
-        assert_eq! { act_0.ck, hex!("2640f52eebcd9e882958951c794250eedb28002c05d7dc2ea0f195406042caf1") };
+        assert_eq! { *act_0.ck, hex!("2640f52eebcd9e882958951c794250eedb28002c05d7dc2ea0f195406042caf1") };
         assert_eq! { act_0.h.as_bytes(), &hex!("8401b3fdcaaa710b5405400536a3d5fd7792fe8e7fe29cd8b687216fe323ecbd") };
     }
 }
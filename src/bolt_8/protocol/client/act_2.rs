@@ -1,37 +1,37 @@
 use crate::bolt_8::{
-    crypto::{decrypt_with_ad, ecdh, hkdf, Sha256Digest},
+    crypto::{decrypt_with_ad, ecdh, hkdf, Secret, Sha256Digest},
     protocol::{client::Act1, ProtocolError},
 };
-use color_eyre::eyre;
-use secp256k1::{PublicKey, SecretKey};
+use secp256k1::PublicKey;
 
 /// Accumulates the state during the Act-2 of the handshake procedure.
-pub struct Act2 {
+#[derive(Debug)]
+pub struct Act2<S> {
     /// The static public key of the local node.
     pub(super) ls_pk: PublicKey,
 
-    /// The static secret key of the local node.
-    pub(super) ls_sk: SecretKey,
+    /// The signer that holds the static secret key of the local node.
+    pub(super) signer: S,
 
     /// The ephemeral public key of the remote node.
     pub(super) re_pk: PublicKey,
 
     /// The chaining key.
-    pub(super) ck: [u8; 32],
+    pub(super) ck: Secret<[u8; 32]>,
 
     /// The intermediate key.
-    pub(super) temp_k2: [u8; 32],
+    pub(super) temp_k2: Secret<[u8; 32]>,
 
     /// The handshake hash.
     pub(super) h: Sha256Digest,
 }
 
-impl Act2 {
+impl<S> Act2<S> {
     /// Initiates the Act-2 of the handshake procedure.
-    pub fn new(act_1: Act1, rm: &[u8; 50]) -> Result<Self, ProtocolError> {
+    pub(super) fn new(act_1: Act1<S>, rm: &[u8; 50]) -> Result<Self, ProtocolError> {
         let Act1 {
             ls_pk,
-            ls_sk,
+            signer,
             le_pk: _,
             le_sk,
             ck,
@@ -62,7 +62,7 @@ impl Act2 {
 
         Ok(Self {
             ls_pk,
-            ls_sk,
+            signer,
             re_pk,
             ck,
             temp_k2,
@@ -74,8 +74,9 @@ impl Act2 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bolt_8::protocol::client::Act0;
+    use crate::{bolt_8::protocol::client::Act0, signer::InMemorySigner};
     use hex_literal::hex;
+    use secp256k1::{SecretKey, SECP256K1};
 
     #[test]
     fn it_accumulates_the_correct_state() {
@@ -96,15 +97,17 @@ mod tests {
 
         let act_0 = Act0::new(rs_pk);
 
-        let act_1 = Act1::new_static(act_0, ls_sk, le_sk).unwrap();
+        let ls_pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        let act_1 = Act1::new_static(act_0, ls_pk, InMemorySigner::new(ls_sk), le_sk).unwrap();
 
         let rm = hex!("0002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae");
 
         let act_2 = Act2::new(act_1, &rm).unwrap();
 
         assert_eq! { act_2.re_pk, re_pk };
-        assert_eq! { act_2.ck, hex!("e89d31033a1b6bf68c07d22e08ea4d7884646c4b60a9528598ccb4ee2c8f56ba") };
-        assert_eq! { act_2.temp_k2, hex!("908b166535c01a935cf1e130a5fe895ab4e6f3ef8855d87e9b7581c4ab663ddc") };
+        assert_eq! { *act_2.ck, hex!("e89d31033a1b6bf68c07d22e08ea4d7884646c4b60a9528598ccb4ee2c8f56ba") };
+        assert_eq! { *act_2.temp_k2, hex!("908b166535c01a935cf1e130a5fe895ab4e6f3ef8855d87e9b7581c4ab663ddc") };
         assert_eq! { act_2.h.as_bytes(), &hex!("90578e247e98674e661013da3c5c1ca6a8c8f48c90b485c0dfa1494e23d56d72") };
     }
 }
@@ -1,10 +1,10 @@
 use crate::bolt_8::{
-    crypto::{ecdh, encrypt_with_ad, hkdf},
-    protocol::{client::Act2, ProtocolError},
+    crypto::{encrypt_with_ad, hkdf, Secret},
+    protocol::{client::Act2, Communication, EncryptedReader, EncryptedWriter, ProtocolError},
 };
-use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 /// Accumulates the state during the Act-3 of the handshake procedure.
+#[derive(Debug)]
 pub struct Act3 {
     /// The static public key encrypted with the ChaCha20 stream cipher.
     pub(super) c: Vec<u8>,
@@ -13,10 +13,10 @@ pub struct Act3 {
     pub(super) t: Vec<u8>,
 
     /// The sending encryption key.
-    pub(super) sk: [u8; 32],
+    pub(super) sk: Secret<[u8; 32]>,
 
     /// The receiving decryption key.
-    pub(super) rk: [u8; 32],
+    pub(super) rk: Secret<[u8; 32]>,
 
     /// The sending nonce.
     pub(super) sn: u64,
@@ -25,19 +25,20 @@ pub struct Act3 {
     pub(super) rn: u64,
 
     /// The sending chaining key.
-    pub(super) sck: [u8; 32],
+    pub(super) sck: Secret<[u8; 32]>,
 
     /// The receiving chaining key.
-    pub(super) rck: [u8; 32],
+    pub(super) rck: Secret<[u8; 32]>,
 }
 
 impl Act3 {
-    /// Initiates the Act-3 of the handshake procedure.
-    pub fn new(act_2: Act2) -> Result<Self, ProtocolError> {
+    /// Initiates the Act-3 of the handshake procedure, with the result of the ECDH
+    /// between the ephemeral key of the remote node and the static key of the local node.
+    pub(super) fn new<S>(act_2: Act2<S>, se: Secret<[u8; 32]>) -> Result<Self, ProtocolError> {
         let Act2 {
             ls_pk,
-            ls_sk,
-            re_pk,
+            signer: _,
+            re_pk: _,
             ck,
             temp_k2,
             mut h,
@@ -47,8 +48,6 @@ impl Act3 {
 
         h.update(&c);
 
-        let se = ecdh(&re_pk, &ls_sk);
-
         let (ck, temp_k3) = hkdf(&ck, &se);
 
         let t = encrypt_with_ad(&temp_k3, 0, h.as_bytes(), b"")?;
@@ -62,21 +61,11 @@ impl Act3 {
             rk,
             sn: 0,
             rn: 0,
-            sck: ck,
+            sck: ck.clone(),
             rck: ck,
         })
     }
 
-    /// Sends the message to the remote node.
-    pub async fn send_message(
-        &self,
-        stream: &mut (impl AsyncWrite + Unpin),
-    ) -> Result<(), ProtocolError> {
-        stream.write_all(&self.message()).await?;
-
-        Ok(())
-    }
-
     // Returns the message to send to the remote node.
     //
     // The handshake is exactly 66 bytes:
@@ -84,7 +73,7 @@ impl Act3 {
     //     - 33 bytes for the static public key encrypted with the ChaCha20 stream cipher;
     //     - 16 bytes for the encrypted public key's tag generated via the AEAD construction;
     //     - 16 bytes for a final authenticating tag;
-    fn message(&self) -> [u8; 66] {
+    pub(super) fn message(&self) -> [u8; 66] {
         let mut m = [0u8; 66];
 
         // Handshake version.
@@ -100,12 +89,35 @@ impl Act3 {
     }
 }
 
+impl From<Act3> for Communication {
+    fn from(act_3: Act3) -> Self {
+        let Act3 {
+            c: _,
+            t: _,
+            sk,
+            rk,
+            sn,
+            rn,
+            sck,
+            rck,
+        } = act_3;
+
+        Self {
+            reader: EncryptedReader::new(rk, rn, rck),
+            writer: EncryptedWriter::new(sk, sn, sck),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::bolt_8::protocol::client::{Act0, Act1};
+    use crate::{
+        bolt_8::protocol::client::{Act0, Act1},
+        signer::{InMemorySigner, NodeSigner},
+    };
     use hex_literal::hex;
-    use secp256k1::{PublicKey, SecretKey};
+    use secp256k1::{PublicKey, SecretKey, SECP256K1};
 
     #[test]
     fn it_accumulates_the_correct_state() {
@@ -123,22 +135,26 @@ mod tests {
 
         let act_0 = Act0::new(rs_pk);
 
-        let act_1 = Act1::new_static(act_0, ls_sk, le_sk).unwrap();
+        let ls_pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        let act_1 = Act1::new_static(act_0, ls_pk, InMemorySigner::new(ls_sk), le_sk).unwrap();
 
         let rm = hex!("0002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae");
 
         let act_2 = Act2::new(act_1, &rm).unwrap();
 
-        let act_3 = Act3::new(act_2).unwrap();
+        let se = act_2.signer.ecdh(&act_2.re_pk).unwrap();
+
+        let act_3 = Act3::new(act_2, se).unwrap();
 
         assert_eq! { act_3.c, hex!("b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c3822") };
         assert_eq! { act_3.t, hex!("8dc68b1c466263b47fdf31e560e139ba") };
-        assert_eq! { act_3.sk, hex!("969ab31b4d288cedf6218839b27a3e2140827047f2c0f01bf5c04435d43511a9") };
-        assert_eq! { act_3.rk, hex!("bb9020b8965f4df047e07f955f3c4b88418984aadc5cdb35096b9ea8fa5c3442") };
+        assert_eq! { *act_3.sk, hex!("969ab31b4d288cedf6218839b27a3e2140827047f2c0f01bf5c04435d43511a9") };
+        assert_eq! { *act_3.rk, hex!("bb9020b8965f4df047e07f955f3c4b88418984aadc5cdb35096b9ea8fa5c3442") };
         assert_eq! { act_3.sn, 0 };
         assert_eq! { act_3.rn, 0 };
-        assert_eq! { act_3.sck, hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01") };
-        assert_eq! { act_3.rck, hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01") };
+        assert_eq! { *act_3.sck, hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01") };
+        assert_eq! { *act_3.rck, hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01") };
         assert_eq! { act_3.message(), hex!("00b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba") };
     }
 }
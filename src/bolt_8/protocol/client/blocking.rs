@@ -0,0 +1,379 @@
+//! This module defines a blocking variant of the client over `std::io` streams,
+//! which follows the same steps as the asynchronous one without requiring a runtime.
+//!
+//! The handshake is not bounded by the `HandshakeTimeouts`, so the read and write timeouts
+//! of the stream should be set instead, e.g. with `TcpStream::set_read_timeout`.
+//! A read that times out during the communication phase keeps the bytes already received,
+//! so the next call resumes reading the same message.
+
+use crate::{
+    bolt_8::protocol::{
+        client::{Act0, Act1, Act2, Act3},
+        Communication, Decrypted, EncryptedReader, EncryptedWriter, ProtocolError,
+    },
+    signer::NodeSigner,
+};
+use secp256k1::PublicKey;
+use std::io::{self, Read, Write};
+
+/// Defines a step-by-step procedure for performing a handshake
+/// and initiating encrypted communication with a remote node over a blocking stream.
+#[derive(Debug)]
+pub struct ClientProtocol<T> {
+    state: T,
+}
+
+impl ClientProtocol<()> {
+    /// Creates a new session with a remote node.
+    pub fn new(rs_pk: PublicKey) -> ClientProtocol<Act0> {
+        ClientProtocol {
+            state: Act0::new(rs_pk),
+        }
+    }
+}
+
+impl ClientProtocol<Act0> {
+    /// Proceeds to the next handshake phase,
+    /// with the signer that holds the static secret key of the local node.
+    pub fn into_next_phase<S: NodeSigner>(
+        self,
+        signer: S,
+    ) -> Result<ClientProtocol<Act1<S>>, ProtocolError> {
+        Ok(ClientProtocol {
+            state: Act1::new(self.state, signer.node_id(), signer)?,
+        })
+    }
+
+    /// Performs the whole handshake with the remote node.
+    pub fn perform_handshake(
+        self,
+        stream: &mut (impl Read + Write),
+        signer: impl NodeSigner,
+    ) -> Result<ClientProtocol<Communication>, ProtocolError> {
+        let client_proto = self.into_next_phase(signer)?;
+        client_proto.send_message(stream)?;
+
+        let client_proto = client_proto.into_next_phase(stream)?.into_next_phase()?;
+        client_proto.send_message(stream)?;
+
+        Ok(client_proto.into_next_phase())
+    }
+}
+
+impl<S> ClientProtocol<Act1<S>> {
+    /// Returns the message to send to the remote node.
+    pub fn next_outbound(&self) -> [u8; 50] {
+        self.state.message()
+    }
+
+    /// Sends the message to the remote node.
+    pub fn send_message(&self, stream: &mut impl Write) -> Result<(), ProtocolError> {
+        stream.write_all(&self.next_outbound())?;
+
+        Ok(())
+    }
+
+    /// Proceeds to the next handshake phase with the message received from the remote node.
+    pub fn process_inbound(
+        self,
+        message: &[u8; 50],
+    ) -> Result<ClientProtocol<Act2<S>>, ProtocolError> {
+        Ok(ClientProtocol {
+            state: Act2::new(self.state, message)?,
+        })
+    }
+
+    /// Proceeds to the next handshake phase.
+    pub fn into_next_phase(
+        self,
+        stream: &mut impl Read,
+    ) -> Result<ClientProtocol<Act2<S>>, ProtocolError> {
+        let mut buf = [0u8; 50];
+        stream.read_exact(&mut buf)?;
+
+        self.process_inbound(&buf)
+    }
+}
+
+impl<S: NodeSigner> ClientProtocol<Act2<S>> {
+    /// Proceeds to the next handshake phase, once the signer has performed its ECDH.
+    pub fn into_next_phase(self) -> Result<ClientProtocol<Act3>, ProtocolError> {
+        let se = self.state.signer.ecdh(&self.state.re_pk)?;
+
+        Ok(ClientProtocol {
+            state: Act3::new(self.state, se)?,
+        })
+    }
+}
+
+impl ClientProtocol<Act3> {
+    /// Returns the message to send to the remote node.
+    pub fn next_outbound(&self) -> [u8; 66] {
+        self.state.message()
+    }
+
+    /// Sends the message to the remote node.
+    pub fn send_message(&self, stream: &mut impl Write) -> Result<(), ProtocolError> {
+        stream.write_all(&self.next_outbound())?;
+
+        Ok(())
+    }
+
+    /// Proceeds to the communication phase.
+    pub fn into_next_phase(self) -> ClientProtocol<Communication> {
+        ClientProtocol {
+            state: Communication::from(self.state),
+        }
+    }
+}
+
+impl ClientProtocol<Communication> {
+    /// Reads a message from the remote node.
+    ///
+    /// Only the bytes of the message are read from the stream, so no bytes of the next message
+    /// are consumed. When the read fails, e.g. with a timeout, the bytes received so far are kept
+    /// by the reader and the next call resumes reading the same message.
+    pub fn read_message(&mut self, stream: &mut impl Read) -> Result<Vec<u8>, ProtocolError> {
+        let mut buf = [0u8; 4096];
+        let mut read = 0;
+
+        loop {
+            match self.state.reader.decrypt(&buf[..read])? {
+                Decrypted::Message(m) => return Ok(m),
+                Decrypted::NeedMore(n) => {
+                    let len = n.min(buf.len());
+
+                    read = match stream.read(&mut buf[..len]) {
+                        Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                        Ok(read) => read,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => 0,
+                        Err(e) => return Err(e.into()),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Sends a message to the remote node.
+    pub fn send_message(
+        &mut self,
+        stream: &mut impl Write,
+        message: &[u8],
+    ) -> Result<(), ProtocolError> {
+        stream.write_all(self.state.writer.encrypt_packet(message)?)?;
+
+        Ok(())
+    }
+
+    /// Splits the communication into its receiving and sending halves.
+    pub fn split(self) -> (EncryptedReader, EncryptedWriter) {
+        self.state.split()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bolt_8::protocol::{client::vectors::*, communication::communications, ServerProtocol},
+        signer::InMemorySigner,
+    };
+    use secp256k1::{SecretKey, SECP256K1};
+    use std::io::Cursor;
+
+    // Returns the client right after the Act-1 of the test vectors.
+    fn client_proto() -> ClientProtocol<Act1<InMemorySigner>> {
+        let act_0 = Act0::new(PublicKey::from_slice(&RS_PK).unwrap());
+        let signer = InMemorySigner::new(SecretKey::from_slice(&LS_SK).unwrap());
+
+        let le_sk = SecretKey::from_slice(&LE_SK).unwrap();
+
+        ClientProtocol {
+            state: Act1::new_static(act_0, signer.node_id(), signer, le_sk).unwrap(),
+        }
+    }
+
+    #[test]
+    fn it_follows_the_test_vectors() {
+        let client_proto = client_proto();
+
+        let mut sent = Vec::new();
+        client_proto.send_message(&mut sent).unwrap();
+        assert_eq! { sent, ACT_1 };
+
+        let client_proto = client_proto
+            .into_next_phase(&mut &ACT_2[..])
+            .unwrap()
+            .into_next_phase()
+            .unwrap();
+
+        let mut sent = Vec::new();
+        client_proto.send_message(&mut sent).unwrap();
+        assert_eq! { sent, ACT_3 };
+
+        let mut client_proto = client_proto.into_next_phase();
+
+        let mut sent = Vec::new();
+        for _ in 0..=1001 {
+            client_proto.send_message(&mut sent, b"hello").unwrap();
+        }
+
+        for (i, output) in OUTPUTS {
+            assert_eq! { sent[i * 39..(i + 1) * 39], output, "message {i}" };
+        }
+    }
+
+    #[test]
+    fn it_rejects_the_invalid_act_2_of_the_test_vectors() {
+        let [bad_version, bad_key, bad_mac] = BAD_ACT_2;
+
+        assert!(matches!(
+            client_proto().process_inbound(&bad_version),
+            Err(ProtocolError::UnknownHandshakeVersion(1))
+        ));
+        assert!(matches!(
+            client_proto().process_inbound(&bad_key),
+            Err(ProtocolError::InvalidPublicKey { .. })
+        ));
+        assert!(matches!(
+            client_proto().process_inbound(&bad_mac),
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+
+    #[test]
+    fn it_reads_the_messages_without_consuming_the_next_ones() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        // The server performs the handshake without IO, and sends two messages.
+        let client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk))
+            .into_next_phase(InMemorySigner::new(ls_sk))
+            .unwrap();
+        let server_proto = ServerProtocol::new(rs_sk)
+            .process_inbound(&client_proto.next_outbound())
+            .unwrap()
+            .into_next_phase()
+            .unwrap();
+        let client_proto = client_proto
+            .process_inbound(&server_proto.next_outbound())
+            .unwrap()
+            .into_next_phase()
+            .unwrap();
+        let (_, mut server_writer) = server_proto
+            .process_inbound(&client_proto.next_outbound())
+            .unwrap()
+            .into_next_phase()
+            .split();
+
+        let mut stream = Cursor::new(
+            [
+                server_writer.encrypt(b"hello").unwrap(),
+                server_writer.encrypt(b"world").unwrap(),
+            ]
+            .concat(),
+        );
+
+        let mut client_proto = client_proto.into_next_phase();
+
+        assert_eq! { client_proto.read_message(&mut stream).unwrap(), b"hello" };
+        assert_eq! { stream.position(), 39 };
+
+        assert_eq! { client_proto.read_message(&mut stream).unwrap(), b"world" };
+        assert!(matches!(
+            client_proto.read_message(&mut stream),
+            Err(ProtocolError::IoError { .. })
+        ));
+    }
+
+    #[test]
+    fn it_resumes_reading_the_message_after_a_timeout() {
+        // A stream that returns its chunks one read at a time, and times out before each one.
+        struct Chunks(Vec<Vec<u8>>, bool);
+
+        impl Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.1 = !self.1;
+
+                if self.1 {
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+
+                let chunk = self.0.remove(0);
+                buf[..chunk.len()].copy_from_slice(&chunk);
+
+                Ok(chunk.len())
+            }
+        }
+
+        let mut client_proto = ClientProtocol {
+            state: communications().0,
+        };
+        let (_, mut remote_writer) = communications().1.split();
+
+        let packet = remote_writer.encrypt(b"hello").unwrap();
+
+        // The header and the body are both received in two chunks.
+        let chunks = [
+            &packet[..10],
+            &packet[10..18],
+            &packet[18..30],
+            &packet[30..],
+        ];
+        let mut stream = Chunks(chunks.map(<[u8]>::to_vec).to_vec(), false);
+
+        for _ in 0..4 {
+            assert!(matches!(
+                client_proto.read_message(&mut stream),
+                Err(ProtocolError::IoError { .. })
+            ));
+        }
+
+        assert_eq! { client_proto.read_message(&mut stream).unwrap(), b"hello" };
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn it_performs_the_handshake_with_an_async_server() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // The server runs on its own runtime, while the client only blocks the current thread.
+        let server = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+
+            runtime.block_on(async {
+                listener.set_nonblocking(true).unwrap();
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                let (mut server_proto, rs_pk) = ServerProtocol::new(rs_sk)
+                    .accept_handshake(&mut stream, &Default::default())
+                    .await
+                    .unwrap();
+
+                let message = server_proto.read_message(&mut stream).await.unwrap();
+                server_proto
+                    .send_message(&mut stream, &message)
+                    .await
+                    .unwrap();
+
+                rs_pk
+            })
+        });
+
+        let mut stream = std::net::TcpStream::connect(address).unwrap();
+
+        let mut client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk))
+            .perform_handshake(&mut stream, InMemorySigner::new(ls_sk))
+            .unwrap();
+
+        client_proto.send_message(&mut stream, b"hello").unwrap();
+        assert_eq! { client_proto.read_message(&mut stream).unwrap(), b"hello" };
+
+        assert_eq! { server.join().unwrap(), PublicKey::from_secret_key(SECP256K1, &ls_sk) };
+    }
+}
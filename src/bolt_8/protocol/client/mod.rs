@@ -1,16 +1,37 @@
+//! This module defines the initiator of the handshake, which connects to a remote node.
+
 mod act_0;
 mod act_1;
 mod act_2;
 mod act_3;
-mod communication;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(test)]
+mod vectors;
 
-use self::{act_0::Act0, act_1::Act1, act_2::Act2, act_3::Act3, communication::Communication};
-use crate::bolt_8::protocol::ProtocolError;
-use secp256k1::{PublicKey, SecretKey};
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+pub use self::{act_0::Act0, act_1::Act1, act_2::Act2, act_3::Act3};
+#[cfg(feature = "async")]
+use crate::{
+    bolt_1::message::{Init, Message},
+    bolt_8::protocol::{HandshakePhase, HandshakeTimeouts, Keepalive},
+    signer::AsyncNodeSigner,
+};
+use crate::{
+    bolt_8::{
+        crypto::Secret,
+        protocol::{Communication, EncryptedReader, EncryptedWriter, KeyLog, ProtocolError},
+    },
+    signer::NodeSigner,
+};
+use secp256k1::PublicKey;
+#[cfg(feature = "fuzzing")]
+use secp256k1::SecretKey;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 /// Defines a step-by-step procedure for performing a handshake
 /// and initiating encrypted communication with a remote node.
+#[derive(Debug)]
 pub struct ClientProtocol<T> {
     state: T,
 }
@@ -25,69 +46,667 @@ impl ClientProtocol<()> {
 }
 
 impl ClientProtocol<Act0> {
-    /// Proceeds to the next handshake phase.
-    pub fn into_next_phase(self, ls_sk: SecretKey) -> Result<ClientProtocol<Act1>, ProtocolError> {
+    /// Proceeds to the next handshake phase,
+    /// with the signer that holds the static secret key of the local node.
+    pub fn into_next_phase<S: NodeSigner>(
+        self,
+        signer: S,
+    ) -> Result<ClientProtocol<Act1<S>>, ProtocolError> {
         Ok(ClientProtocol {
-            state: Act1::new(self.state, ls_sk)?,
+            state: Act1::new(self.state, signer.node_id(), signer)?,
         })
     }
+
+    /// Proceeds to the next handshake phase with a fixed ephemeral key,
+    /// so that the fuzz targets reach the next phases deterministically.
+    #[cfg(feature = "fuzzing")]
+    pub fn into_next_phase_with_ephemeral_key<S: NodeSigner>(
+        self,
+        signer: S,
+        le_sk: SecretKey,
+    ) -> Result<ClientProtocol<Act1<S>>, ProtocolError> {
+        Ok(ClientProtocol {
+            state: Act1::new_static(self.state, signer.node_id(), signer, le_sk)?,
+        })
+    }
+
+    /// Performs the whole handshake with the remote node.
+    ///
+    /// Fails with a timeout when an act or the whole handshake does not complete in time.
+    #[cfg(feature = "async")]
+    pub async fn perform_handshake(
+        self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        signer: impl AsyncNodeSigner,
+        timeouts: &HandshakeTimeouts,
+    ) -> Result<ClientProtocol<Communication>, ProtocolError> {
+        let deadline = timeouts.start();
+
+        let client_proto = ClientProtocol {
+            state: Act1::new(self.state, signer.node_id(), signer)?,
+        };
+
+        timeouts
+            .run(
+                HandshakePhase::Act1,
+                deadline,
+                client_proto.send_message(stream),
+            )
+            .await?;
+
+        let client_proto = timeouts
+            .run(
+                HandshakePhase::Act2,
+                deadline,
+                client_proto.into_next_phase(stream),
+            )
+            .await?;
+
+        // The signer may be remote, so its ECDH is bounded by the timeout of the act as well.
+        let client_proto = timeouts
+            .run(HandshakePhase::Act3, deadline, async {
+                let client_proto = client_proto.into_next_phase().await?;
+                client_proto.send_message(stream).await?;
+
+                Ok(client_proto)
+            })
+            .await?;
+
+        Ok(client_proto.into_next_phase())
+    }
 }
 
-impl ClientProtocol<Act1> {
+impl<S> ClientProtocol<Act1<S>> {
+    /// Returns the message to send to the remote node.
+    pub fn next_outbound(&self) -> [u8; 50] {
+        self.state.message()
+    }
+
     /// Sends the message to the remote node.
+    #[cfg(feature = "async")]
     pub async fn send_message(
         &self,
         stream: &mut (impl AsyncWrite + Unpin),
     ) -> Result<(), ProtocolError> {
-        self.state.send_message(stream).await
+        stream.write_all(&self.next_outbound()).await?;
+
+        Ok(())
+    }
+
+    /// Proceeds to the next handshake phase with the message received from the remote node.
+    pub fn process_inbound(
+        self,
+        message: &[u8; 50],
+    ) -> Result<ClientProtocol<Act2<S>>, ProtocolError> {
+        Ok(ClientProtocol {
+            state: Act2::new(self.state, message)?,
+        })
     }
 
     /// Proceeds to the next handshake phase.
+    #[cfg(feature = "async")]
     pub async fn into_next_phase(
         self,
         stream: &mut (impl AsyncRead + Unpin),
-    ) -> Result<ClientProtocol<Act2>, ProtocolError> {
+    ) -> Result<ClientProtocol<Act2<S>>, ProtocolError> {
         let mut buf = [0u8; 50];
         stream.read_exact(&mut buf).await?;
 
-        Ok(ClientProtocol {
-            state: Act2::new(self.state, &buf)?,
-        })
+        self.process_inbound(&buf)
     }
 }
 
-impl ClientProtocol<Act2> {
-    /// Proceeds to the next handshake phase.
-    pub fn into_next_phase(self) -> Result<ClientProtocol<Act3>, ProtocolError> {
+impl<S> ClientProtocol<Act2<S>> {
+    /// Returns the signer that holds the static secret key of the local node.
+    pub fn signer(&self) -> &S {
+        &self.state.signer
+    }
+
+    /// Returns the ephemeral public key of the remote node,
+    /// with which the signer performs the ECDH of the next handshake phase.
+    pub fn remote_ephemeral_key(&self) -> PublicKey {
+        self.state.re_pk
+    }
+
+    /// Proceeds to the next handshake phase with the result of the ECDH
+    /// between the ephemeral key of the remote node and the static key of the local node.
+    pub fn process_ecdh(self, se: Secret<[u8; 32]>) -> Result<ClientProtocol<Act3>, ProtocolError> {
         Ok(ClientProtocol {
-            state: Act3::new(self.state)?,
+            state: Act3::new(self.state, se)?,
         })
     }
 }
 
+#[cfg(feature = "async")]
+impl<S: AsyncNodeSigner> ClientProtocol<Act2<S>> {
+    /// Proceeds to the next handshake phase, once the signer has performed its ECDH.
+    pub async fn into_next_phase(self) -> Result<ClientProtocol<Act3>, ProtocolError> {
+        let se = self.state.signer.ecdh(&self.state.re_pk).await?;
+
+        self.process_ecdh(se)
+    }
+}
+
 impl ClientProtocol<Act3> {
+    /// Returns the message to send to the remote node.
+    pub fn next_outbound(&self) -> [u8; 66] {
+        self.state.message()
+    }
+
     /// Sends the message to the remote node.
+    #[cfg(feature = "async")]
     pub async fn send_message(
         &self,
         stream: &mut (impl AsyncWrite + Unpin),
     ) -> Result<(), ProtocolError> {
-        self.state.send_message(stream).await
+        stream.write_all(&self.next_outbound()).await?;
+
+        Ok(())
     }
 
     /// Proceeds to the communication phase.
     pub fn into_next_phase(self) -> ClientProtocol<Communication> {
         ClientProtocol {
-            state: Communication::new(self.state),
+            state: Communication::from(self.state),
         }
     }
 }
 
 impl ClientProtocol<Communication> {
     /// Reads a message from the remote node.
+    #[cfg(feature = "async")]
     pub async fn read_message(
         &mut self,
         stream: &mut (impl AsyncRead + Unpin),
     ) -> Result<Vec<u8>, ProtocolError> {
         self.state.read_message(stream).await
     }
+
+    /// Sends a message to the remote node.
+    #[cfg(feature = "async")]
+    pub async fn send_message(
+        &mut self,
+        stream: &mut (impl AsyncWrite + Unpin),
+        message: &[u8],
+    ) -> Result<(), ProtocolError> {
+        self.state.send_message(stream, message).await
+    }
+
+    /// Sends the init message of the local node and reads the init message of the remote node.
+    #[cfg(feature = "async")]
+    pub async fn exchange_init(
+        &mut self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        init: &Init,
+        chain_hash: &[u8; 32],
+    ) -> Result<Init, ProtocolError> {
+        self.state.exchange_init(stream, init, chain_hash).await
+    }
+
+    /// Reads the next message from the remote node that is not a ping or a pong.
+    #[cfg(feature = "async")]
+    pub async fn read_message_with_keepalive(
+        &mut self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        keepalive: &mut Keepalive,
+    ) -> Result<Message, ProtocolError> {
+        self.state
+            .read_message_with_keepalive(stream, keepalive)
+            .await
+    }
+
+    /// Splits the communication into its receiving and sending halves.
+    pub fn split(self) -> (EncryptedReader, EncryptedWriter) {
+        self.state.split()
+    }
+
+    /// Writes the keys of the session to the key log, to decrypt captured traffic offline.
+    pub fn set_key_log(&mut self, key_log: KeyLog) {
+        self.state.set_key_log(key_log);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "async")]
+    use crate::{
+        bolt_1::{
+            message::{Ping, Warning},
+            network::Network,
+        },
+        bolt_8::protocol::mock_peer::{Act2Message, MockPeer, Step, HANDSHAKE},
+        bolt_9::features::{Feature, Features},
+        signer::SignerError,
+    };
+    use crate::{
+        bolt_8::{crypto::read_after_drop, protocol::client::vectors::*},
+        signer::InMemorySigner,
+    };
+    use secp256k1::{SecretKey, SECP256K1};
+    #[cfg(feature = "async")]
+    use tokio::{io::DuplexStream, task::JoinHandle, time::Duration};
+
+    #[cfg(feature = "async")]
+    type Peer = JoinHandle<Result<Vec<Message>, ProtocolError>>;
+
+    // A signer that rejects every ECDH.
+    #[cfg(feature = "async")]
+    struct RejectingSigner(PublicKey);
+
+    #[cfg(feature = "async")]
+    impl AsyncNodeSigner for RejectingSigner {
+        fn node_id(&self) -> PublicKey {
+            self.0
+        }
+
+        async fn ecdh(&self, _: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+            Err(SignerError::Rejected(1))
+        }
+    }
+
+    // Returns a client connected to a mock peer following the script.
+    #[cfg(feature = "async")]
+    fn mock_peer(
+        script: impl IntoIterator<Item = Step>,
+    ) -> (ClientProtocol<Act0>, DuplexStream, Peer) {
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let (stream, peer) = MockPeer::new(rs_sk, script).spawn();
+
+        let client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk));
+
+        (client_proto, stream, peer)
+    }
+
+    // Performs the handshake with a mock peer following the script.
+    #[cfg(feature = "async")]
+    async fn handshake(
+        script: impl IntoIterator<Item = Step>,
+    ) -> (
+        Result<ClientProtocol<Communication>, ProtocolError>,
+        DuplexStream,
+        Peer,
+    ) {
+        let (client_proto, mut stream, peer) = mock_peer(script);
+        let signer = InMemorySigner::new(SecretKey::new(&mut secp256k1::rand::thread_rng()));
+
+        let result = client_proto
+            .perform_handshake(&mut stream, signer, &HandshakeTimeouts::default())
+            .await;
+
+        (result, stream, peer)
+    }
+
+    // Completes the handshake with a mock peer, which then follows the script.
+    #[cfg(feature = "async")]
+    async fn session(
+        script: impl IntoIterator<Item = Step>,
+    ) -> (ClientProtocol<Communication>, DuplexStream, Peer) {
+        let (result, stream, peer) = handshake(HANDSHAKE.into_iter().chain(script)).await;
+
+        (result.unwrap(), stream, peer)
+    }
+
+    // Exchanges the init messages with a mock peer, which sends the message instead of its init.
+    #[cfg(feature = "async")]
+    async fn exchange_init(message: Step) -> Result<Init, ProtocolError> {
+        let (mut client_proto, mut stream, _peer) = session([message]).await;
+
+        client_proto
+            .exchange_init(
+                &mut stream,
+                &Init::default(),
+                &Network::Bitcoin.chain_hash(),
+            )
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    fn init(features: &[u8], network: Network) -> Message {
+        let mut init = Init {
+            features: Features::from_bytes(features),
+            ..Default::default()
+        };
+        init.set_networks(&[network.chain_hash()]);
+
+        Message::Init(init)
+    }
+
+    // Returns the client right after the Act-1 of the test vectors.
+    fn client_proto() -> ClientProtocol<Act1<InMemorySigner>> {
+        let act_0 = Act0::new(PublicKey::from_slice(&RS_PK).unwrap());
+        let signer = InMemorySigner::new(SecretKey::from_slice(&LS_SK).unwrap());
+
+        let le_sk = SecretKey::from_slice(&LE_SK).unwrap();
+
+        ClientProtocol {
+            state: Act1::new_static(act_0, NodeSigner::node_id(&signer), signer, le_sk).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_follows_the_test_vectors() {
+        let client_proto = client_proto();
+
+        let mut sent = Vec::new();
+        client_proto.send_message(&mut sent).await.unwrap();
+        assert_eq! { sent, ACT_1 };
+
+        let client_proto = client_proto
+            .into_next_phase(&mut &ACT_2[..])
+            .await
+            .unwrap()
+            .into_next_phase()
+            .await
+            .unwrap();
+
+        let mut sent = Vec::new();
+        client_proto.send_message(&mut sent).await.unwrap();
+        assert_eq! { sent, ACT_3 };
+
+        let mut client_proto = client_proto.into_next_phase();
+
+        let mut sent = Vec::new();
+        for _ in 0..=1001 {
+            client_proto
+                .send_message(&mut sent, b"hello")
+                .await
+                .unwrap();
+        }
+
+        for (i, output) in OUTPUTS {
+            assert_eq! { sent[i * 39..(i + 1) * 39], output, "message {i}" };
+        }
+    }
+
+    #[test]
+    fn it_follows_the_test_vectors_without_io() {
+        let client_proto = client_proto();
+        assert_eq! { client_proto.next_outbound(), ACT_1 };
+
+        // The ECDH is performed by the caller, so that the signer may be reached in any way.
+        let client_proto = client_proto.process_inbound(&ACT_2).unwrap();
+        let re_pk = client_proto.remote_ephemeral_key();
+        let se = NodeSigner::ecdh(client_proto.signer(), &re_pk).unwrap();
+
+        let client_proto = client_proto.process_ecdh(se).unwrap();
+        assert_eq! { client_proto.next_outbound(), ACT_3 };
+
+        let (_, mut writer) = client_proto.into_next_phase().split();
+
+        let sent: Vec<_> = (0..=1001)
+            .map(|_| writer.encrypt(b"hello").unwrap())
+            .collect();
+
+        for (i, output) in OUTPUTS {
+            assert_eq! { sent[i], output, "message {i}" };
+        }
+    }
+
+    #[test]
+    fn it_rejects_the_invalid_act_2_of_the_test_vectors() {
+        let [bad_version, bad_key, bad_mac] = BAD_ACT_2;
+
+        assert!(matches!(
+            client_proto().process_inbound(&bad_version),
+            Err(ProtocolError::UnknownHandshakeVersion(1))
+        ));
+        assert!(matches!(
+            client_proto().process_inbound(&bad_key),
+            Err(ProtocolError::InvalidPublicKey { .. })
+        ));
+        assert!(matches!(
+            client_proto().process_inbound(&bad_mac),
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+
+    #[test]
+    fn it_zeroizes_the_secrets_on_drop() {
+        assert_ne! { *client_proto().state.ck, [0; 32] };
+
+        assert_eq! { read_after_drop(client_proto(), |x| &*x.state.ck), [0; 32] };
+        assert_eq! { read_after_drop(client_proto(), |x| &*x.state.le_sk).secret_bytes(), [1; 32] };
+    }
+
+    #[test]
+    fn it_redacts_the_secrets() {
+        let ls_sk = SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let rs_pk =
+            PublicKey::from_secret_key(SECP256K1, &SecretKey::from_slice(&[0x22; 32]).unwrap());
+
+        let client_proto = ClientProtocol::new(rs_pk)
+            .into_next_phase(InMemorySigner::new(ls_sk))
+            .unwrap();
+
+        let debug = format!("{client_proto:?}");
+
+        assert!(debug.contains("ls_sk: Secret([REDACTED])"));
+        assert!(debug.contains("ck: Secret([REDACTED])"));
+        assert!(!debug.contains(&hex::encode(ls_sk.secret_bytes())));
+        assert!(!debug.contains(&hex::encode(*client_proto.state.ck)));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_exchanges_the_init_messages_with_a_mock_peer_over_tcp() {
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let remote_init = init(&[], Network::Bitcoin);
+
+        let script = HANDSHAKE
+            .into_iter()
+            .chain([Step::Read, Step::Send(remote_init.clone())]);
+        let (address, peer) = MockPeer::new(rs_sk, script).listen().await;
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        let signer = InMemorySigner::new(SecretKey::new(&mut secp256k1::rand::thread_rng()));
+
+        let mut client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk))
+            .perform_handshake(&mut stream, signer, &HandshakeTimeouts::default())
+            .await
+            .unwrap();
+
+        let result = client_proto
+            .exchange_init(
+                &mut stream,
+                &Init::default(),
+                &Network::Bitcoin.chain_hash(),
+            )
+            .await;
+
+        assert_eq! { Message::Init(result.unwrap()), remote_init };
+        assert_eq! { peer.await.unwrap().unwrap(), [Message::Init(Init::default())] };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_on_an_invalid_act_2_of_a_mock_peer() {
+        let act_2 = |m| [Step::ReadAct1, Step::SendAct2(m), Step::Stall];
+
+        assert!(matches!(
+            handshake(act_2(Act2Message::BadVersion)).await.0,
+            Err(ProtocolError::UnknownHandshakeVersion(1))
+        ));
+        assert!(matches!(
+            handshake(act_2(Act2Message::BadKey)).await.0,
+            Err(ProtocolError::InvalidPublicKey { .. })
+        ));
+        assert!(matches!(
+            handshake(act_2(Act2Message::BadMac)).await.0,
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_when_a_mock_peer_disconnects_mid_act() {
+        let script = [Step::ReadAct1, Step::SendAct2(Act2Message::Truncated(20))];
+
+        assert!(matches!(
+            handshake(script).await.0,
+            Err(ProtocolError::IoError { .. })
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(feature = "async")]
+    async fn it_times_out_when_a_mock_peer_stalls_the_handshake() {
+        let script = [Step::ReadAct1, Step::Stall];
+
+        assert!(matches!(
+            handshake(script).await.0,
+            Err(ProtocolError::Timeout {
+                phase: HandshakePhase::Act2
+            })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_when_the_signer_rejects_the_ecdh() {
+        let (client_proto, mut stream, peer) = mock_peer(HANDSHAKE);
+
+        let signer = RejectingSigner(PublicKey::from_secret_key(
+            SECP256K1,
+            &SecretKey::new(&mut secp256k1::rand::thread_rng()),
+        ));
+
+        let result = client_proto
+            .perform_handshake(&mut stream, signer, &HandshakeTimeouts::default())
+            .await;
+
+        assert!(matches!(result, Err(ProtocolError::SignerFailure { .. })));
+
+        // The mock peer never receives the Act-3 message.
+        drop(stream);
+        assert!(matches!(
+            peer.await.unwrap(),
+            Err(ProtocolError::IoError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_on_a_message_with_a_wrong_mac() {
+        let (mut client_proto, mut stream, _peer) =
+            session([Step::SendBadMac(b"hello".to_vec()), Step::Stall]).await;
+
+        assert!(matches!(
+            client_proto.read_message(&mut stream).await,
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_on_a_malformed_message() {
+        assert!(matches!(
+            exchange_init(Step::SendBytes(vec![0x00])).await,
+            Err(ProtocolError::InvalidMessage { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_when_a_mock_peer_does_not_send_its_init() {
+        let ping = Message::Ping(Ping::default());
+
+        assert!(matches!(
+            exchange_init(Step::Send(ping)).await,
+            Err(ProtocolError::UnexpectedMessage {
+                expected: Init::TYPE,
+                got: Ping::TYPE
+            })
+        ));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_on_an_incompatible_init_of_a_mock_peer() {
+        let mut missing_dependency = Features::default();
+        missing_dependency.set(Feature::BASIC_MPP.optional_bit());
+
+        assert!(matches!(
+            exchange_init(Step::Send(init(
+                &hex_literal::hex!("40000000"),
+                Network::Bitcoin
+            )))
+            .await,
+            Err(ProtocolError::UnknownRequiredFeature(30))
+        ));
+        assert!(matches!(
+            exchange_init(Step::Send(init(
+                &missing_dependency.to_bytes(),
+                Network::Bitcoin
+            )))
+            .await,
+            Err(ProtocolError::InvalidFeatures { .. })
+        ));
+        assert!(matches!(
+            exchange_init(Step::Send(init(&[], Network::Testnet))).await,
+            Err(ProtocolError::ChainHashMismatch { .. })
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(feature = "async")]
+    async fn it_keeps_the_session_with_a_mock_peer_alive() {
+        let warning = Message::Warning(Warning {
+            channel_id: [0; 32],
+            data: b"hello".to_vec(),
+        });
+
+        let (mut client_proto, mut stream, _peer) = session([
+            Step::AnswerPing { extra_bytes: 0 },
+            Step::Send(warning.clone()),
+            Step::Stall,
+        ])
+        .await;
+
+        let mut keepalive = Keepalive::new(Duration::from_secs(60), Duration::from_secs(30));
+
+        let result = client_proto
+            .read_message_with_keepalive(&mut stream, &mut keepalive)
+            .await;
+        assert_eq! { result.unwrap(), warning };
+
+        // The mock peer stalls the next ping.
+        assert!(matches!(
+            client_proto
+                .read_message_with_keepalive(&mut stream, &mut keepalive)
+                .await,
+            Err(ProtocolError::PongTimeout)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg(feature = "async")]
+    async fn it_fails_on_a_pong_of_the_wrong_length() {
+        let (mut client_proto, mut stream, _peer) =
+            session([Step::AnswerPing { extra_bytes: 1 }, Step::Stall]).await;
+
+        let result = client_proto
+            .read_message_with_keepalive(&mut stream, &mut Keepalive::default())
+            .await;
+
+        assert!(
+            matches!(result, Err(ProtocolError::InvalidPongLength { want, got }) if got == want + 1)
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_to_send_a_message_that_is_too_long() {
+        let (mut client_proto, mut stream, _peer) = session([Step::Stall]).await;
+
+        let result = client_proto
+            .send_message(&mut stream, &[0; u16::MAX as usize + 1])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::InvalidMessageLength(_))
+        ));
+    }
 }
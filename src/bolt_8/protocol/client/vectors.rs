@@ -0,0 +1,58 @@
+//! The BOLT-8 test vectors of the initiator, which are run against both variants of the client.
+//!
+//! See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+use hex_literal::hex;
+
+/// The static public key of the responder.
+pub(super) const RS_PK: [u8; 33] =
+    hex!("028d7500dd4c12685d1f568b4c2b5048e8534b873319f3a8daa612b469132ec7f7");
+
+/// The static secret key of the initiator.
+pub(super) const LS_SK: [u8; 32] =
+    hex!("1111111111111111111111111111111111111111111111111111111111111111");
+
+/// The ephemeral secret key of the initiator.
+pub(super) const LE_SK: [u8; 32] =
+    hex!("1212121212121212121212121212121212121212121212121212121212121212");
+
+pub(super) const ACT_1: [u8; 50] = hex!("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+pub(super) const ACT_2: [u8; 50] = hex!("0002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae");
+
+pub(super) const ACT_3: [u8; 66] = hex!("00b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba");
+
+/// The Act-2 messages the initiator must reject: a bad version, a bad key and a bad MAC.
+pub(super) const BAD_ACT_2: [[u8; 50]; 3] = [
+    hex!("0102466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae"),
+    hex!("0004466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae"),
+    hex!("0002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730af"),
+];
+
+/// The encrypted "hello" messages sent by the initiator, keyed by their index.
+pub(super) const OUTPUTS: [(usize, [u8; 39]); 6] = [
+    (
+        0,
+        hex!("cf2b30ddf0cf3f80e7c35a6e6730b59fe802473180f396d88a8fb0db8cbcf25d2f214cf9ea1d95"),
+    ),
+    (
+        1,
+        hex!("72887022101f0b6753e0c7de21657d35a4cb2a1f5cde2650528bbc8f837d0f0d7ad833b1a256a1"),
+    ),
+    (
+        500,
+        hex!("178cb9d7387190fa34db9c2d50027d21793c9bc2d40b1e14dcf30ebeeeb220f48364f7a4c68bf8"),
+    ),
+    (
+        501,
+        hex!("1b186c57d44eb6de4c057c49940d79bb838a145cb528d6e8fd26dbe50a60ca2c104b56b60e45bd"),
+    ),
+    (
+        1000,
+        hex!("4a2f3cc3b5e78ddb83dcb426d9863d9d9a723b0337c89dd0b005d89f8d3c05c52b76b29b740f09"),
+    ),
+    (
+        1001,
+        hex!("2ecd8c8a5629d0d02ab457a0fdd0f7b90a192cd46be5ecb6ca570bfc5e268338b1a16cf4ef2d36"),
+    ),
+];
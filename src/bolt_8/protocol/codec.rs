@@ -0,0 +1,173 @@
+use crate::bolt_8::protocol::{
+    ClientProtocol, Communication, Decrypted, EncryptedReader, EncryptedWriter, ProtocolError,
+    ServerProtocol,
+};
+use bytes::BytesMut;
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Frames the encrypted messages exchanged with a remote node,
+/// to be used with `tokio_util::codec::Framed` once the handshake is completed.
+///
+/// The decoder yields the decrypted message bodies,
+/// while the encoder accepts the plaintext messages to encrypt.
+#[derive(Debug)]
+pub struct Bolt8Codec {
+    /// The receiving half of the communication.
+    reader: EncryptedReader,
+
+    /// The sending half of the communication.
+    writer: EncryptedWriter,
+}
+
+impl From<Communication> for Bolt8Codec {
+    fn from(communication: Communication) -> Self {
+        let (reader, writer) = communication.split();
+
+        Self { reader, writer }
+    }
+}
+
+impl From<ClientProtocol<Communication>> for Bolt8Codec {
+    fn from(client_proto: ClientProtocol<Communication>) -> Self {
+        let (reader, writer) = client_proto.split();
+
+        Self { reader, writer }
+    }
+}
+
+impl From<ServerProtocol<Communication>> for Bolt8Codec {
+    fn from(server_proto: ServerProtocol<Communication>) -> Self {
+        let (reader, writer) = server_proto.split();
+
+        Self { reader, writer }
+    }
+}
+
+impl Decoder for Bolt8Codec {
+    type Item = Vec<u8>;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Vec<u8>>, ProtocolError> {
+        // The received bytes are moved to the reader, which keeps the incomplete
+        // header or body until the next call.
+        let bytes = src.split();
+
+        match self.reader.decrypt(&bytes)? {
+            Decrypted::Message(m) => Ok(Some(m)),
+            Decrypted::NeedMore(n) => {
+                src.reserve(n);
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Vec<u8>>, ProtocolError> {
+        match self.decode(src)? {
+            Some(m) => Ok(Some(m)),
+            None if self.reader.is_empty() => Ok(None),
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for Bolt8Codec {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, message: T, dst: &mut BytesMut) -> Result<(), ProtocolError> {
+        self.writer.encrypt_into(message.as_ref(), dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::protocol::communication::communications;
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    #[tokio::test]
+    async fn it_sends_and_receives_framed_messages() {
+        let (local, remote) = communications();
+        let (local_stream, remote_stream) = tokio::io::duplex(4096);
+
+        let mut local = tokio_util::codec::Framed::new(local_stream, Bolt8Codec::from(local));
+        let mut remote = tokio_util::codec::Framed::new(remote_stream, Bolt8Codec::from(remote));
+
+        local.send(b"hello").await.unwrap();
+        local.send(vec![0x42; 1000]).await.unwrap();
+
+        assert_eq! { remote.next().await.unwrap().unwrap(), b"hello" };
+        assert_eq! { remote.next().await.unwrap().unwrap(), vec![0x42; 1000] };
+
+        remote.send(b"world").await.unwrap();
+
+        assert_eq! { local.next().await.unwrap().unwrap(), b"world" };
+    }
+
+    #[tokio::test]
+    async fn it_decodes_messages_split_across_reads() {
+        let (local, remote) = communications();
+        let (mut local_stream, remote_stream) = tokio::io::duplex(1024);
+
+        let (_, mut writer) = local.split();
+
+        let mut packets = Vec::new();
+        for m in [&b"hello"[..], &[0x42; 300], b""] {
+            packets.extend(writer.encrypt(m).unwrap());
+        }
+
+        let mut remote = FramedRead::new(remote_stream, Bolt8Codec::from(remote));
+
+        let sending = tokio::spawn(async move {
+            // The chunks cut through the headers and the bodies of the messages.
+            for chunk in packets.chunks(7) {
+                local_stream.write_all(chunk).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        assert_eq! { remote.next().await.unwrap().unwrap(), b"hello" };
+        assert_eq! { remote.next().await.unwrap().unwrap(), vec![0x42; 300] };
+        assert_eq! { remote.next().await.unwrap().unwrap(), b"" };
+
+        sending.await.unwrap();
+
+        // The stream ends cleanly once the sender is dropped between two messages.
+        assert!(remote.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn it_fails_when_the_stream_ends_within_a_message() {
+        let (local, remote) = communications();
+        let (mut local_stream, remote_stream) = tokio::io::duplex(1024);
+
+        let (_, mut writer) = local.split();
+        let packet = writer.encrypt(b"hello").unwrap();
+
+        local_stream.write_all(&packet[..20]).await.unwrap();
+        drop(local_stream);
+
+        let mut remote = FramedRead::new(remote_stream, Bolt8Codec::from(remote));
+
+        assert!(matches!(
+            remote.next().await,
+            Some(Err(ProtocolError::IoError { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_messages_that_are_too_long() {
+        let (local, _) = communications();
+
+        let mut local = FramedWrite::new(Vec::new(), Bolt8Codec::from(local));
+
+        let result = local.send(vec![0; u16::MAX as usize + 1]).await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::InvalidMessageLength(_))
+        ));
+    }
+}
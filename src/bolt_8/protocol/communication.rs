@@ -0,0 +1,678 @@
+use crate::bolt_8::{
+    crypto::{hkdf, Cipher, Secret},
+    protocol::{KeyLog, ProtocolError},
+};
+use bytes::BufMut;
+#[cfg(feature = "async")]
+use std::io;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The number of times a key is used before it gets rotated.
+///
+/// Since every message requires two operations, one for the length
+/// and one for the body, the keys are rotated every 500 messages.
+const KEY_ROTATION_INTERVAL: u64 = 1000;
+
+/// Defines the communication phase of the protocol.
+///
+/// Contains the required state to perform encrypted communication with a remote node.
+#[derive(Debug)]
+pub struct Communication {
+    /// The receiving half of the communication.
+    pub(super) reader: EncryptedReader,
+
+    /// The sending half of the communication.
+    pub(super) writer: EncryptedWriter,
+}
+
+// Returns the state of both nodes right after a handshake, to test the communication phase
+// without performing one.
+#[cfg(test)]
+pub(crate) fn communications() -> (Communication, Communication) {
+    let (k1, k2, ck) = ([0x01; 32], [0x02; 32], [0x03; 32]);
+
+    let local = Communication {
+        reader: EncryptedReader::new(Secret::new(k2), 0, Secret::new(ck)),
+        writer: EncryptedWriter::new(Secret::new(k1), 0, Secret::new(ck)),
+    };
+
+    let remote = Communication {
+        reader: EncryptedReader::new(Secret::new(k1), 0, Secret::new(ck)),
+        writer: EncryptedWriter::new(Secret::new(k2), 0, Secret::new(ck)),
+    };
+
+    (local, remote)
+}
+
+impl Communication {
+    /// Reads a message from the remote node.
+    ///
+    /// This method is cancel safe: the bytes received before the cancellation are kept
+    /// and the next call resumes reading the same message.
+    #[cfg(feature = "async")]
+    pub async fn read_message(
+        &mut self,
+        stream: &mut (impl AsyncRead + Unpin),
+    ) -> Result<Vec<u8>, ProtocolError> {
+        self.reader.read_message(stream).await
+    }
+
+    /// Sends a message to the remote node.
+    #[cfg(feature = "async")]
+    pub async fn send_message(
+        &mut self,
+        stream: &mut (impl AsyncWrite + Unpin),
+        message: &[u8],
+    ) -> Result<(), ProtocolError> {
+        self.writer.send_message(stream, message).await
+    }
+
+    /// Splits the communication into its receiving and sending halves,
+    /// which can be moved into separate tasks to read and send concurrently.
+    pub fn split(self) -> (EncryptedReader, EncryptedWriter) {
+        (self.reader, self.writer)
+    }
+
+    /// Writes the keys of both directions to the key log from now on,
+    /// including the ones derived by the key rotation.
+    pub fn set_key_log(&mut self, key_log: KeyLog) {
+        self.reader.key_log = Some(key_log.clone());
+        self.writer.key_log = Some(key_log);
+    }
+}
+
+/// Contains the required state to decrypt the messages received from a remote node.
+#[derive(Debug)]
+pub struct EncryptedReader {
+    /// The receiving decryption key.
+    pub(super) rk: Secret<[u8; 32]>,
+
+    /// The receiving nonce.
+    pub(super) rn: u64,
+
+    /// The receiving chaining key.
+    pub(super) rck: Secret<[u8; 32]>,
+
+    /// The cipher of the receiving key, kept until the key is rotated.
+    pub(super) rcipher: Cipher,
+
+    /// The bytes received from the remote node, decrypted in place.
+    pub(super) rbuf: Vec<u8>,
+
+    /// The number of bytes at the start of the buffer that were already decrypted and returned.
+    pub(super) rpos: usize,
+
+    /// The length of the message being received, once its header is decrypted.
+    pub(super) rl: Option<usize>,
+
+    /// The key log the receiving keys are written to, if any.
+    pub(super) key_log: Option<KeyLog>,
+}
+
+impl EncryptedReader {
+    // Creates the receiving half with the key, nonce and chaining key of the handshake.
+    pub(super) fn new(rk: Secret<[u8; 32]>, rn: u64, rck: Secret<[u8; 32]>) -> Self {
+        Self {
+            rcipher: Cipher::new(&rk),
+            rk,
+            rn,
+            rck,
+            rbuf: Vec::new(),
+            rpos: 0,
+            rl: None,
+            key_log: None,
+        }
+    }
+
+    /// Reads a message from the remote node.
+    ///
+    /// This method is cancel safe: the bytes received before the cancellation are kept
+    /// and the next call resumes reading the same message.
+    #[cfg(feature = "async")]
+    pub async fn read_message(
+        &mut self,
+        stream: &mut (impl AsyncRead + Unpin),
+    ) -> Result<Vec<u8>, ProtocolError> {
+        loop {
+            if let Decrypted::Message(p) = self.decrypt(&[])? {
+                return Ok(p);
+            }
+
+            self.compact();
+
+            if stream.read_buf(&mut self.rbuf).await? == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+        }
+    }
+
+    /// Returns whether no bytes of an incomplete message are waiting to be decrypted.
+    pub fn is_empty(&self) -> bool {
+        self.rpos == self.rbuf.len() && self.rl.is_none()
+    }
+
+    /// Appends the bytes received from the remote node and decrypts the next message.
+    ///
+    /// The header is decrypted as soon as its 18 bytes are received,
+    /// and the body once its length and its 16 bytes tag are received.
+    /// When several messages are received at once, this method should be called
+    /// with no bytes until more bytes are needed.
+    ///
+    /// The message is copied out of the buffer of the reader,
+    /// which `decrypt_in_place` avoids.
+    pub fn decrypt(&mut self, bytes: &[u8]) -> Result<Decrypted, ProtocolError> {
+        Ok(match self.decrypt_in_place(bytes)? {
+            Decrypted::Message(m) => Decrypted::Message(m.to_vec()),
+            Decrypted::NeedMore(n) => Decrypted::NeedMore(n),
+        })
+    }
+
+    /// Appends the bytes received from the remote node and decrypts the next message in place,
+    /// like `decrypt` but without any allocation once the buffer of the reader is large enough.
+    ///
+    /// The message is borrowed from the buffer of the reader until the next call.
+    pub fn decrypt_in_place(&mut self, bytes: &[u8]) -> Result<Decrypted<&[u8]>, ProtocolError> {
+        if !bytes.is_empty() {
+            self.compact();
+            self.rbuf.extend_from_slice(bytes);
+        }
+
+        let l = match self.rl {
+            Some(l) => l,
+            None => {
+                let available = self.rbuf.len() - self.rpos;
+
+                if available < 18 {
+                    return Ok(Decrypted::NeedMore(18 - available));
+                }
+
+                let (lc, tag) = self.rbuf[self.rpos..self.rpos + 18].split_at_mut(2);
+                let tag: &[u8; 16] = (&*tag).try_into().unwrap();
+                self.rcipher.decrypt_in_place(self.rn, &[], lc, tag)?;
+
+                // The first header decrypted with a key identifies it in the key log.
+                if let (Some(key_log), 0) = (&self.key_log, self.rn) {
+                    key_log.log(tag, &self.rk);
+                }
+
+                advance(&mut self.rk, &mut self.rck, &mut self.rn, &mut self.rcipher);
+
+                let l = u16::from_be_bytes([lc[0], lc[1]]) as usize;
+                self.rpos += 18;
+                self.rl = Some(l);
+
+                l
+            }
+        };
+
+        let available = self.rbuf.len() - self.rpos;
+
+        if available < l + 16 {
+            return Ok(Decrypted::NeedMore(l + 16 - available));
+        }
+
+        self.rl = None;
+
+        let start = self.rpos;
+        self.rpos += l + 16;
+
+        let (c, tag) = self.rbuf[start..self.rpos].split_at_mut(l);
+        self.rcipher
+            .decrypt_in_place(self.rn, &[], c, (&*tag).try_into().unwrap())?;
+        advance(&mut self.rk, &mut self.rck, &mut self.rn, &mut self.rcipher);
+
+        Ok(Decrypted::Message(c))
+    }
+
+    // Drops the bytes of the messages already returned, before more bytes are appended.
+    //
+    // The buffer is only shifted once per batch of received bytes, rather than once per message,
+    // and not at all when the received bytes end on a message boundary.
+    fn compact(&mut self) {
+        self.rbuf.drain(..self.rpos);
+        self.rpos = 0;
+    }
+}
+
+/// The outcome of decrypting the bytes received from the remote node,
+/// whose message is either owned or borrowed from the reader.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decrypted<M = Vec<u8>> {
+    /// A complete message has been decrypted.
+    Message(M),
+
+    /// The given number of bytes is still missing to decrypt the header or the body.
+    NeedMore(usize),
+}
+
+/// Contains the required state to encrypt the messages sent to a remote node.
+#[derive(Debug)]
+pub struct EncryptedWriter {
+    /// The sending encryption key.
+    pub(super) sk: Secret<[u8; 32]>,
+
+    /// The sending nonce.
+    pub(super) sn: u64,
+
+    /// The sending chaining key.
+    pub(super) sck: Secret<[u8; 32]>,
+
+    /// The cipher of the sending key, kept until the key is rotated.
+    pub(super) scipher: Cipher,
+
+    /// The buffer the packets are encrypted into, reused across the messages.
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    pub(super) sbuf: Vec<u8>,
+
+    /// The key log the sending keys are written to, if any.
+    pub(super) key_log: Option<KeyLog>,
+}
+
+impl EncryptedWriter {
+    // Creates the sending half with the key, nonce and chaining key of the handshake.
+    pub(super) fn new(sk: Secret<[u8; 32]>, sn: u64, sck: Secret<[u8; 32]>) -> Self {
+        Self {
+            scipher: Cipher::new(&sk),
+            sk,
+            sn,
+            sck,
+            #[cfg(any(feature = "async", feature = "blocking"))]
+            sbuf: Vec::new(),
+            key_log: None,
+        }
+    }
+
+    /// Sends a message to the remote node.
+    #[cfg(feature = "async")]
+    pub async fn send_message(
+        &mut self,
+        stream: &mut (impl AsyncWrite + Unpin),
+        message: &[u8],
+    ) -> Result<(), ProtocolError> {
+        stream.write_all(self.encrypt_packet(message)?).await?;
+
+        Ok(())
+    }
+
+    // Encrypts the packet into the reused buffer, and returns it to be sent to the remote node.
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    pub(super) fn encrypt_packet(&mut self, m: &[u8]) -> Result<&[u8], ProtocolError> {
+        let mut buf = std::mem::take(&mut self.sbuf);
+        buf.clear();
+
+        let result = self.encrypt_into(m, &mut buf);
+        self.sbuf = buf;
+        result?;
+
+        Ok(&self.sbuf)
+    }
+
+    /// Returns the encrypted packet to send to the remote node.
+    pub fn encrypt(&mut self, m: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        let mut buf = Vec::with_capacity(m.len() + 34);
+        self.encrypt_into(m, &mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Appends the encrypted packet to send to the remote node to the buffer,
+    /// encrypting the header and the body in place.
+    ///
+    /// The packet consists of:
+    ///     - 18 bytes for the encrypted big-endian length of the message and its tag;
+    ///     - the encrypted message followed by its 16 bytes tag;
+    pub fn encrypt_into(
+        &mut self,
+        m: &[u8],
+        buf: &mut (impl BufMut + AsMut<[u8]>),
+    ) -> Result<(), ProtocolError> {
+        let l: u16 = m.len().try_into().map_err(|_| {
+            ProtocolError::InvalidMessageLength(format!(
+                "want at most {} bytes, got {}",
+                u16::MAX,
+                m.len(),
+            ))
+        })?;
+
+        for x in [&l.to_be_bytes()[..], m] {
+            let start = buf.as_mut().len();
+            buf.put_slice(x);
+
+            let tag = self
+                .scipher
+                .encrypt_in_place(self.sn, &[], &mut buf.as_mut()[start..])?;
+
+            // A key is first used for a header, since every message takes two nonces,
+            // whose tag identifies the key in the key log.
+            if let (Some(key_log), 0) = (&self.key_log, self.sn) {
+                key_log.log(&tag, &self.sk);
+            }
+
+            advance(&mut self.sk, &mut self.sck, &mut self.sn, &mut self.scipher);
+
+            buf.put_slice(&tag);
+        }
+
+        Ok(())
+    }
+}
+
+// Increments the nonce of the key and performs the key rotation when required.
+//
+// The rotation is performed as follows:
+//     - ck', k' = HKDF(ck, k);
+//     - the chaining key is replaced with ck';
+//     - the key is replaced with k', along with its cipher;
+//     - the nonce is reset to 0;
+fn advance(k: &mut Secret<[u8; 32]>, ck: &mut Secret<[u8; 32]>, n: &mut u64, cipher: &mut Cipher) {
+    *n += 1;
+
+    if *n == KEY_ROTATION_INTERVAL {
+        (*ck, *k) = hkdf(ck, &*k);
+        *n = 0;
+        *cipher = Cipher::new(k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::crypto::read_after_drop;
+    use hex_literal::hex;
+
+    // The keys of the initiator right after the handshake from the BOLT-8 test vectors.
+    const SK: [u8; 32] = hex!("969ab31b4d288cedf6218839b27a3e2140827047f2c0f01bf5c04435d43511a9");
+    const RK: [u8; 32] = hex!("bb9020b8965f4df047e07f955f3c4b88418984aadc5cdb35096b9ea8fa5c3442");
+    const CK: [u8; 32] = hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01");
+
+    // See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+    // Returns the state of the initiator right after the handshake.
+    fn communication() -> Communication {
+        Communication {
+            reader: EncryptedReader::new(Secret::new(RK), 0, Secret::new(CK)),
+            writer: EncryptedWriter::new(Secret::new(SK), 0, Secret::new(CK)),
+        }
+    }
+
+    // Returns the receiving side of the remote node, which mirrors the sending side of the initiator.
+    fn receiver() -> EncryptedReader {
+        EncryptedReader::new(Secret::new(SK), 0, Secret::new(CK))
+    }
+
+    // The encrypted "hello" messages from the BOLT-8 test vectors, keyed by their index.
+    const OUTPUTS: [(usize, [u8; 39]); 6] = [
+        (
+            0,
+            hex!("cf2b30ddf0cf3f80e7c35a6e6730b59fe802473180f396d88a8fb0db8cbcf25d2f214cf9ea1d95"),
+        ),
+        (
+            1,
+            hex!("72887022101f0b6753e0c7de21657d35a4cb2a1f5cde2650528bbc8f837d0f0d7ad833b1a256a1"),
+        ),
+        (
+            500,
+            hex!("178cb9d7387190fa34db9c2d50027d21793c9bc2d40b1e14dcf30ebeeeb220f48364f7a4c68bf8"),
+        ),
+        (
+            501,
+            hex!("1b186c57d44eb6de4c057c49940d79bb838a145cb528d6e8fd26dbe50a60ca2c104b56b60e45bd"),
+        ),
+        (
+            1000,
+            hex!("4a2f3cc3b5e78ddb83dcb426d9863d9d9a723b0337c89dd0b005d89f8d3c05c52b76b29b740f09"),
+        ),
+        (
+            1001,
+            hex!("2ecd8c8a5629d0d02ab457a0fdd0f7b90a192cd46be5ecb6ca570bfc5e268338b1a16cf4ef2d36"),
+        ),
+    ];
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_sends_the_correct_messages() {
+        let mut communication = communication();
+
+        let mut messages = Vec::new();
+
+        for _ in 0..=1001 {
+            let mut stream = Vec::new();
+            communication
+                .send_message(&mut stream, b"hello")
+                .await
+                .unwrap();
+            messages.push(stream);
+        }
+
+        for (i, output) in OUTPUTS {
+            assert_eq! { messages[i], output, "message {i}" };
+        }
+
+        // The key has been rotated twice, after the 500th and the 1000th message.
+        assert_eq! { communication.writer.sn, 4 };
+    }
+
+    #[test]
+    fn it_encrypts_into_a_reused_buffer() {
+        let mut communication = communication();
+
+        let mut buf = Vec::new();
+        let mut messages = Vec::new();
+
+        for _ in 0..=1001 {
+            buf.clear();
+            communication
+                .writer
+                .encrypt_into(b"hello", &mut buf)
+                .unwrap();
+            messages.push(buf.clone());
+        }
+
+        for (i, output) in OUTPUTS {
+            assert_eq! { messages[i], output, "message {i}" };
+        }
+
+        // The packet is appended after the bytes already in the buffer.
+        let mut buf = vec![0xff; 3];
+        communication
+            .writer
+            .encrypt_into(b"hello", &mut buf)
+            .unwrap();
+
+        assert_eq! { buf[..3], [0xff; 3] };
+        assert_eq! { buf.len(), 3 + 39 };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_reads_the_correct_messages() {
+        let mut sender = communication();
+
+        let mut stream = Vec::new();
+
+        for _ in 0..=1001 {
+            sender.send_message(&mut stream, b"hello").await.unwrap();
+        }
+
+        let mut receiver = receiver();
+
+        for (i, output) in OUTPUTS {
+            assert_eq! { stream[i * 39..(i + 1) * 39], output, "message {i}" };
+        }
+
+        let mut stream = stream.as_slice();
+
+        for _ in 0..=1001 {
+            let message = receiver.read_message(&mut stream).await.unwrap();
+            assert_eq! { message, b"hello" };
+        }
+
+        assert!(stream.is_empty());
+        assert!(receiver.is_empty());
+        assert_eq! { *receiver.rk, *sender.writer.sk };
+        assert_eq! { *receiver.rck, *sender.writer.sck };
+        assert_eq! { receiver.rn, 4 };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_resumes_reading_after_a_cancellation() {
+        let mut sender = communication();
+
+        let mut packet = Vec::new();
+        sender.send_message(&mut packet, b"hello").await.unwrap();
+
+        let mut receiver = receiver();
+
+        let (mut local, mut remote) = tokio::io::duplex(1024);
+
+        // Only the header and a part of the body are received before the read is cancelled.
+        local.write_all(&packet[..20]).await.unwrap();
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            receiver.read_message(&mut remote),
+        )
+        .await;
+        assert!(result.is_err());
+
+        local.write_all(&packet[20..]).await.unwrap();
+
+        let message = receiver.read_message(&mut remote).await.unwrap();
+        assert_eq! { message, b"hello" };
+    }
+
+    #[test]
+    fn it_decrypts_the_messages_incrementally() {
+        let mut sender = communication().writer;
+        let mut receiver = receiver();
+
+        let packet = sender.encrypt(b"hello").unwrap();
+
+        // The missing bytes of the header are reported first, then those of the body.
+        for (i, byte) in packet[..packet.len() - 1].iter().enumerate() {
+            let want = if i < 17 { 17 - i } else { 38 - i };
+            assert_eq! { receiver.decrypt(&[*byte]).unwrap(), Decrypted::NeedMore(want), "byte {i}" };
+        }
+
+        assert_eq! {
+            receiver.decrypt(&packet[packet.len() - 1..]).unwrap(),
+            Decrypted::Message(b"hello".to_vec())
+        };
+
+        // Several messages received at once are decrypted one at a time.
+        let mut packets = sender.encrypt(b"hello").unwrap();
+        packets.extend(sender.encrypt(b"world").unwrap());
+
+        assert_eq! { receiver.decrypt(&packets).unwrap(), Decrypted::Message(b"hello".to_vec()) };
+        assert_eq! { receiver.decrypt(&[]).unwrap(), Decrypted::Message(b"world".to_vec()) };
+        assert_eq! { receiver.decrypt(&[]).unwrap(), Decrypted::NeedMore(18) };
+    }
+
+    #[test]
+    fn it_decrypts_the_messages_in_place() {
+        let mut sender = communication().writer;
+        let mut receiver = receiver();
+
+        let packets: Vec<_> = (0..3u8)
+            .flat_map(|i| sender.encrypt(&[i; 32]).unwrap())
+            .collect();
+
+        // The first message and the header of the second one are decrypted where they were received.
+        assert_eq! { receiver.decrypt_in_place(&packets[..100]).unwrap(), Decrypted::Message(&[0; 32][..]) };
+        assert_eq! { receiver.decrypt_in_place(&[]).unwrap(), Decrypted::NeedMore(32) };
+        assert_eq! { receiver.rpos, 84 };
+
+        // The decrypted bytes are only dropped once more bytes are received.
+        assert_eq! { receiver.decrypt_in_place(&packets[100..]).unwrap(), Decrypted::Message(&[1; 32][..]) };
+        assert_eq! { receiver.decrypt_in_place(&[]).unwrap(), Decrypted::Message(&[2; 32][..]) };
+        assert_eq! { receiver.decrypt_in_place(&[]).unwrap(), Decrypted::NeedMore(18) };
+        assert_eq! { receiver.rbuf.len(), 16 + 98 };
+        assert!(receiver.is_empty());
+
+        // The buffer does not grow with the number of messages received.
+        let packet = sender.encrypt(&[3; 32]).unwrap();
+
+        assert_eq! { receiver.decrypt_in_place(&packet).unwrap(), Decrypted::Message(&[3; 32][..]) };
+        assert_eq! { receiver.rbuf.len(), 66 };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_rejects_messages_that_are_too_long() {
+        let mut communication = communication();
+
+        let mut stream = Vec::new();
+        let result = communication
+            .send_message(&mut stream, &[0; u16::MAX as usize + 1])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::InvalidMessageLength(_))
+        ));
+        assert!(stream.is_empty());
+        assert_eq! { communication.writer.sn, 0 };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_reads_and_sends_concurrently_after_a_split() {
+        let (local, mut remote) = communications();
+
+        let (local_stream, mut remote_stream) = tokio::io::duplex(64);
+        let (mut read_half, mut write_half) = tokio::io::split(local_stream);
+
+        let (mut reader, mut writer) = local.split();
+
+        // The buffer of the stream is too small to send all the messages before reading any.
+        let sending = tokio::spawn(async move {
+            for i in 0..100u8 {
+                writer
+                    .send_message(&mut write_half, &[i; 32])
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let reading = tokio::spawn(async move {
+            for i in 0..100u8 {
+                let message = reader.read_message(&mut read_half).await.unwrap();
+                assert_eq! { message, [i; 32] };
+            }
+        });
+
+        for i in 0..100u8 {
+            let message = remote.read_message(&mut remote_stream).await.unwrap();
+            assert_eq! { message, [i; 32] };
+
+            remote
+                .send_message(&mut remote_stream, &[i; 32])
+                .await
+                .unwrap();
+        }
+
+        sending.await.unwrap();
+        reading.await.unwrap();
+    }
+
+    #[test]
+    fn it_zeroizes_and_redacts_the_keys() {
+        assert_eq! { read_after_drop(communication(), |x| &*x.writer.sk), [0; 32] };
+        assert_eq! { read_after_drop(communication(), |x| &*x.writer.sck), [0; 32] };
+        assert_eq! { read_after_drop(communication().reader, |x| &*x.rk), [0; 32] };
+        assert_eq! { read_after_drop(communication().reader, |x| &*x.rck), [0; 32] };
+
+        let communication = communication();
+        let debug = format!("{communication:?}");
+
+        for key in [
+            &communication.writer.sk,
+            &communication.reader.rk,
+            &communication.writer.sck,
+        ] {
+            assert!(!debug.contains(&hex::encode(**key)));
+        }
+    }
+}
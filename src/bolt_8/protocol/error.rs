@@ -1,5 +1,9 @@
-use crate::bolt_8::crypto::CryptoError;
-use color_eyre::eyre;
+use crate::{
+    bolt_1::message::MessageError,
+    bolt_8::{crypto::CryptoError, protocol::HandshakePhase},
+    bolt_9::features::FeatureError,
+    signer::SignerError,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ProtocolError {
@@ -17,6 +21,36 @@ pub enum ProtocolError {
 
     #[error("Invalid message length: {0}")]
     InvalidMessageLength(String),
+
+    #[error("The message is not valid")]
+    InvalidMessage { source: eyre::Report },
+
+    #[error("Expected a message of type '{expected}', got '{got}'")]
+    UnexpectedMessage { expected: u16, got: u16 },
+
+    #[error("The remote node requires the unknown feature bit '{0}'")]
+    UnknownRequiredFeature(usize),
+
+    #[error("The features are not valid")]
+    InvalidFeatures { source: eyre::Report },
+
+    #[error("The remote node does not operate on the '{chain_hash}' chain")]
+    ChainHashMismatch { chain_hash: String },
+
+    #[error("The remote node did not respond to the ping in time")]
+    PongTimeout,
+
+    #[error("Expected a pong of {want} bytes, got {got}")]
+    InvalidPongLength { want: usize, got: usize },
+
+    #[error("The remote node sent the unknown even message type '{0}'")]
+    UnknownEvenMessage(u16),
+
+    #[error("The handshake timed out during {phase}")]
+    Timeout { phase: HandshakePhase },
+
+    #[error("The signer has failed")]
+    SignerFailure { source: eyre::Report },
 }
 
 impl From<CryptoError> for ProtocolError {
@@ -27,8 +61,32 @@ impl From<CryptoError> for ProtocolError {
     }
 }
 
-impl From<tokio::io::Error> for ProtocolError {
-    fn from(e: tokio::io::Error) -> Self {
+impl From<SignerError> for ProtocolError {
+    fn from(e: SignerError) -> Self {
+        Self::SignerFailure {
+            source: eyre::Report::new(e),
+        }
+    }
+}
+
+impl From<MessageError> for ProtocolError {
+    fn from(e: MessageError) -> Self {
+        Self::InvalidMessage {
+            source: eyre::Report::new(e),
+        }
+    }
+}
+
+impl From<FeatureError> for ProtocolError {
+    fn from(e: FeatureError) -> Self {
+        Self::InvalidFeatures {
+            source: eyre::Report::new(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ProtocolError {
+    fn from(e: std::io::Error) -> Self {
         Self::IoError {
             source: eyre::Report::new(e),
         }
@@ -0,0 +1,304 @@
+#[cfg(feature = "async")]
+use crate::bolt_8::protocol::ProtocolError;
+use std::fmt;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use tokio::time::{self, Duration, Instant};
+
+/// An act of the handshake, during which the handshake may time out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakePhase {
+    /// The initiator sends its ephemeral key to the responder.
+    Act1,
+
+    /// The responder sends its ephemeral key to the initiator.
+    Act2,
+
+    /// The initiator sends its static key to the responder.
+    Act3,
+}
+
+impl fmt::Display for HandshakePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Act1 => write!(f, "act one"),
+            Self::Act2 => write!(f, "act two"),
+            Self::Act3 => write!(f, "act three"),
+        }
+    }
+}
+
+/// Bounds the time a remote node has to complete the handshake.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, Copy)]
+pub struct HandshakeTimeouts {
+    /// The time each act has to send or receive its message.
+    act_timeout: Duration,
+
+    /// The time the whole handshake has to complete.
+    handshake_timeout: Duration,
+}
+
+#[cfg(feature = "async")]
+impl HandshakeTimeouts {
+    /// Creates timeouts for each act and for the whole handshake.
+    pub fn new(act_timeout: Duration, handshake_timeout: Duration) -> Self {
+        Self {
+            act_timeout,
+            handshake_timeout,
+        }
+    }
+
+    // Returns the deadline of the whole handshake, when it starts now.
+    pub(super) fn start(&self) -> Instant {
+        Instant::now() + self.handshake_timeout
+    }
+
+    // Runs the act until its own deadline or the deadline of the whole handshake,
+    // whichever comes first.
+    pub(super) async fn run<T>(
+        &self,
+        phase: HandshakePhase,
+        handshake_deadline: Instant,
+        act: impl Future<Output = Result<T, ProtocolError>>,
+    ) -> Result<T, ProtocolError> {
+        let deadline = handshake_deadline.min(Instant::now() + self.act_timeout);
+
+        time::timeout_at(deadline, act)
+            .await
+            .map_err(|_| ProtocolError::Timeout { phase })?
+    }
+}
+
+#[cfg(feature = "async")]
+impl Default for HandshakeTimeouts {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10), Duration::from_secs(30))
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use super::*;
+    use crate::{
+        bolt_8::protocol::{ClientProtocol, ServerProtocol},
+        signer::InMemorySigner,
+    };
+    use secp256k1::{PublicKey, SecretKey, SECP256K1};
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+
+    // A stream that stops reading and writing for good once a number of bytes went through.
+    struct StallingStream {
+        inner: DuplexStream,
+        read_limit: usize,
+        write_limit: usize,
+    }
+
+    impl AsyncRead for StallingStream {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if self.read_limit == 0 {
+                return Poll::Pending;
+            }
+
+            let mut bytes = vec![0; self.read_limit.min(buf.remaining())];
+            let mut limited = ReadBuf::new(&mut bytes);
+
+            match Pin::new(&mut self.inner).poll_read(cx, &mut limited) {
+                Poll::Ready(Ok(())) => {
+                    let n = limited.filled().len();
+                    buf.put_slice(&bytes[..n]);
+                    self.read_limit -= n;
+
+                    Poll::Ready(Ok(()))
+                }
+                x => x,
+            }
+        }
+    }
+
+    impl AsyncWrite for StallingStream {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            if self.write_limit == 0 {
+                return Poll::Pending;
+            }
+
+            let n = self.write_limit.min(buf.len());
+
+            match Pin::new(&mut self.inner).poll_write(cx, &buf[..n]) {
+                Poll::Ready(Ok(n)) => {
+                    self.write_limit -= n;
+
+                    Poll::Ready(Ok(n))
+                }
+                x => x,
+            }
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.inner).poll_shutdown(cx)
+        }
+    }
+
+    fn keys() -> (SecretKey, SecretKey) {
+        (
+            SecretKey::from_slice(&[0x11; 32]).unwrap(),
+            SecretKey::from_slice(&[0x22; 32]).unwrap(),
+        )
+    }
+
+    // Returns a stream to a server that performs the handshake as long as the stream lets it.
+    fn stream_to_server(read_limit: usize, write_limit: usize) -> StallingStream {
+        let (server_sk, _) = keys();
+        let (client_stream, mut server_stream) = tokio::io::duplex(1024);
+
+        tokio::spawn(async move {
+            let _ = ServerProtocol::new(server_sk)
+                .accept_handshake(&mut server_stream, &HandshakeTimeouts::default())
+                .await;
+        });
+
+        StallingStream {
+            inner: client_stream,
+            read_limit,
+            write_limit,
+        }
+    }
+
+    // Returns a stream to a client that performs the handshake as long as the stream lets it.
+    fn stream_to_client(read_limit: usize, write_limit: usize) -> StallingStream {
+        let (server_sk, client_sk) = keys();
+        let (mut client_stream, server_stream) = tokio::io::duplex(1024);
+
+        tokio::spawn(async move {
+            let _ = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &server_sk))
+                .perform_handshake(
+                    &mut client_stream,
+                    InMemorySigner::new(client_sk),
+                    &HandshakeTimeouts::default(),
+                )
+                .await;
+        });
+
+        StallingStream {
+            inner: server_stream,
+            read_limit,
+            write_limit,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_performs_the_handshake_within_the_timeouts() {
+        let (server_sk, client_sk) = keys();
+        let (mut client_stream, mut server_stream) = tokio::io::duplex(1024);
+
+        let timeouts = HandshakeTimeouts::default();
+
+        let (client_proto, server_proto) = tokio::join!(
+            ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &server_sk))
+                .perform_handshake(
+                    &mut client_stream,
+                    InMemorySigner::new(client_sk),
+                    &timeouts
+                ),
+            ServerProtocol::new(server_sk).accept_handshake(&mut server_stream, &timeouts),
+        );
+
+        let mut client_proto = client_proto.unwrap();
+        let (mut server_proto, rs_pk) = server_proto.unwrap();
+
+        assert_eq! { rs_pk, PublicKey::from_secret_key(SECP256K1, &client_sk) };
+
+        client_proto
+            .send_message(&mut client_stream, b"hello")
+            .await
+            .unwrap();
+
+        assert_eq! { server_proto.read_message(&mut server_stream).await.unwrap(), b"hello" };
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_times_out_when_the_client_stalls_at_each_act() {
+        let (server_sk, client_sk) = keys();
+        let timeouts = HandshakeTimeouts::new(Duration::from_secs(10), Duration::from_secs(30));
+
+        for (read_limit, write_limit, phase) in [
+            (usize::MAX, 0, HandshakePhase::Act1),
+            (0, 50, HandshakePhase::Act2),
+            (usize::MAX, 50, HandshakePhase::Act3),
+        ] {
+            let mut stream = stream_to_server(read_limit, write_limit);
+
+            let start = Instant::now();
+
+            let result = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &server_sk))
+                .perform_handshake(&mut stream, InMemorySigner::new(client_sk), &timeouts)
+                .await;
+
+            assert!(matches!(result, Err(ProtocolError::Timeout { phase: p }) if p == phase));
+            assert_eq! { start.elapsed(), Duration::from_secs(10) };
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_times_out_when_the_server_stalls_at_each_act() {
+        let (server_sk, _) = keys();
+        let timeouts = HandshakeTimeouts::new(Duration::from_secs(10), Duration::from_secs(30));
+
+        for (read_limit, write_limit, phase) in [
+            (0, usize::MAX, HandshakePhase::Act1),
+            (50, 0, HandshakePhase::Act2),
+            (50, usize::MAX, HandshakePhase::Act3),
+        ] {
+            let mut stream = stream_to_client(read_limit, write_limit);
+
+            let start = Instant::now();
+
+            let result = ServerProtocol::new(server_sk)
+                .accept_handshake(&mut stream, &timeouts)
+                .await;
+
+            assert!(matches!(result, Err(ProtocolError::Timeout { phase: p }) if p == phase));
+            assert_eq! { start.elapsed(), Duration::from_secs(10) };
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_times_out_when_the_whole_handshake_takes_too_long() {
+        let (server_sk, client_sk) = keys();
+        let timeouts = HandshakeTimeouts::new(Duration::from_secs(10), Duration::from_secs(5));
+
+        let mut stream = stream_to_server(0, 50);
+
+        let start = Instant::now();
+
+        let result = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &server_sk))
+            .perform_handshake(&mut stream, InMemorySigner::new(client_sk), &timeouts)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::Timeout {
+                phase: HandshakePhase::Act2
+            })
+        ));
+        assert_eq! { start.elapsed(), Duration::from_secs(5) };
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    bolt_1::message::{Init, Message},
+    bolt_8::protocol::{Communication, ProtocolError},
+    bolt_9::features::Context,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+impl Communication {
+    /// Sends the init message of the local node and reads the init message of the remote node.
+    ///
+    /// Fails when the local node sets features that are not allowed in the init message,
+    /// or when the remote node requires an unknown feature, misses a feature dependency
+    /// or does not operate on the chain of the local node.
+    pub async fn exchange_init(
+        &mut self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        init: &Init,
+        chain_hash: &[u8; 32],
+    ) -> Result<Init, ProtocolError> {
+        init.combined_features().validate_context(Context::Init)?;
+
+        let m = Message::Init(init.clone()).encode()?;
+        self.send_message(stream, &m).await?;
+
+        let m = self.read_message(stream).await?;
+
+        let remote_init = match Message::decode(&m)? {
+            Message::Init(x) => x,
+            x => {
+                return Err(ProtocolError::UnexpectedMessage {
+                    expected: Init::TYPE,
+                    got: x.message_type(),
+                })
+            }
+        };
+
+        validate_init(&remote_init, chain_hash)?;
+
+        Ok(remote_init)
+    }
+}
+
+// Checks that the remote node is compatible with the local node:
+//     - all the required features of the remote node must be known;
+//     - all the dependencies of the features of the remote node must be set;
+//     - the chain of the local node must be listed, if the remote node lists any;
+fn validate_init(init: &Init, chain_hash: &[u8; 32]) -> Result<(), ProtocolError> {
+    let features = init.combined_features();
+
+    if let Some(bit) = features.unknown_required_bits().first() {
+        return Err(ProtocolError::UnknownRequiredFeature(*bit));
+    }
+
+    features.validate_dependencies()?;
+
+    if let Some(networks) = init.networks()? {
+        if !networks.contains(chain_hash) {
+            return Err(ProtocolError::ChainHashMismatch {
+                chain_hash: hex::encode(chain_hash),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bolt_1::{message::Ping, network::Network},
+        bolt_8::protocol::communication::communications,
+        bolt_9::features::{Feature, Features},
+    };
+    use hex_literal::hex;
+
+    // Exchanges the init messages and returns the result of the local node.
+    async fn exchange(
+        local_init: Init,
+        remote_message: Message,
+        chain_hash: [u8; 32],
+    ) -> Result<Init, ProtocolError> {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let m = remote_message.encode().unwrap();
+        remote.send_message(&mut remote_stream, &m).await.unwrap();
+
+        let result = local
+            .exchange_init(&mut local_stream, &local_init, &chain_hash)
+            .await;
+
+        let m = remote.read_message(&mut remote_stream).await.unwrap();
+        assert_eq! { Message::decode(&m).unwrap(), Message::Init(local_init) };
+
+        result
+    }
+
+    fn init(features: &[u8], networks: &[[u8; 32]]) -> Init {
+        let mut init = Init {
+            features: Features::from_bytes(features),
+            ..Default::default()
+        };
+
+        if !networks.is_empty() {
+            init.set_networks(networks);
+        }
+
+        init
+    }
+
+    #[tokio::test]
+    async fn it_exchanges_the_init_messages() {
+        let chain_hash = Network::Bitcoin.chain_hash();
+
+        // The init message received from a public node.
+        let remote_init = init(&hex!("a088288a6981"), &[chain_hash]);
+
+        let result = exchange(
+            init(&[], &[chain_hash]),
+            Message::Init(remote_init.clone()),
+            chain_hash,
+        )
+        .await;
+
+        assert_eq! { result.unwrap(), remote_init };
+    }
+
+    #[tokio::test]
+    async fn it_accepts_remote_nodes_without_networks() {
+        let chain_hash = Network::Regtest.chain_hash();
+
+        let result = exchange(init(&[], &[]), Message::Init(init(&[], &[])), chain_hash).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_fails_on_unknown_required_features() {
+        let chain_hash = Network::Bitcoin.chain_hash();
+
+        // Bit 31 is odd and can be ignored, while bit 30 is unknown and required.
+        let result = exchange(
+            init(&[], &[]),
+            Message::Init(init(&hex!("c0000000"), &[])),
+            chain_hash,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::UnknownRequiredFeature(30))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_fails_on_missing_feature_dependencies() {
+        let mut features = Features::default();
+        features.set(Feature::BASIC_MPP.optional_bit());
+
+        let result = exchange(
+            init(&[], &[]),
+            Message::Init(init(&features.to_bytes(), &[])),
+            Network::Bitcoin.chain_hash(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(ProtocolError::InvalidFeatures { .. })));
+    }
+
+    #[tokio::test]
+    async fn it_fails_on_local_features_not_allowed_in_init() {
+        let (mut local, _remote) = communications();
+        let (mut local_stream, _remote_stream) = tokio::io::duplex(1024);
+
+        let mut features = Features::default();
+        features.set(Feature::OPTION_PAYMENT_METADATA.optional_bit());
+
+        let result = local
+            .exchange_init(
+                &mut local_stream,
+                &init(&features.to_bytes(), &[]),
+                &Network::Bitcoin.chain_hash(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(ProtocolError::InvalidFeatures { .. })));
+    }
+
+    #[tokio::test]
+    async fn it_fails_on_chain_hash_mismatch() {
+        let result = exchange(
+            init(&[], &[]),
+            Message::Init(init(&[], &[Network::Testnet.chain_hash()])),
+            Network::Regtest.chain_hash(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::ChainHashMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_fails_on_unexpected_messages() {
+        let result = exchange(
+            init(&[], &[]),
+            Message::Ping(Ping::default()),
+            Network::Bitcoin.chain_hash(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::UnexpectedMessage {
+                expected: Init::TYPE,
+                got: Ping::TYPE
+            })
+        ));
+    }
+}
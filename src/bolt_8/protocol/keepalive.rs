@@ -0,0 +1,362 @@
+use crate::{
+    bolt_1::message::{Message, Ping, Pong},
+    bolt_8::protocol::{Communication, ProtocolError},
+};
+use secp256k1::rand::{thread_rng, Rng};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    time::{self, Duration, Instant},
+};
+
+/// The largest `num_pong_bytes` a node responds to.
+///
+/// Pings requesting more bytes are used as padding and must not be answered.
+const MAX_NUM_PONG_BYTES: u16 = 65531;
+
+/// Keeps a session alive by sending pings and expecting the matching pongs in return.
+pub struct Keepalive {
+    /// The time between two pings.
+    ping_interval: Duration,
+
+    /// The time the remote node has to respond to a ping.
+    pong_timeout: Duration,
+
+    /// The time of the next ping.
+    next_ping: Instant,
+
+    /// The length of the expected pong and its deadline, while a ping is outstanding.
+    pending_pong: Option<(usize, Instant)>,
+}
+
+impl Keepalive {
+    /// Creates a keepalive that sends the first ping after the given interval.
+    pub fn new(ping_interval: Duration, pong_timeout: Duration) -> Self {
+        Self {
+            ping_interval,
+            pong_timeout,
+            next_ping: Instant::now() + ping_interval,
+            pending_pong: None,
+        }
+    }
+
+    /// Returns the time at which the keepalive has to act next,
+    /// with `on_deadline` unless a message is received before.
+    pub fn deadline(&self) -> Instant {
+        match self.pending_pong {
+            Some((_, deadline)) => deadline,
+            None => self.next_ping,
+        }
+    }
+
+    /// Returns the ping to send once the deadline is reached.
+    ///
+    /// Fails when the remote node has not responded to the previous ping in time.
+    pub fn on_deadline(&mut self) -> Result<Message, ProtocolError> {
+        if self.pending_pong.is_some() {
+            return Err(ProtocolError::PongTimeout);
+        }
+
+        let num_pong_bytes = thread_rng().gen_range(0..=MAX_NUM_PONG_BYTES);
+
+        let now = Instant::now();
+        self.pending_pong = Some((num_pong_bytes as usize, now + self.pong_timeout));
+        self.next_ping = now + self.ping_interval;
+
+        Ok(Message::Ping(Ping {
+            num_pong_bytes,
+            ignored: vec![],
+        }))
+    }
+
+    /// Handles the pings and the pongs received from the remote node,
+    /// and passes the other messages through.
+    ///
+    /// Fails when the remote node responds with a pong of the wrong length, or sends a message
+    /// of an unknown even type, which BOLT-1 requires to fail the connection on.
+    pub fn on_message(&mut self, message: Message) -> Result<KeepaliveOutcome, ProtocolError> {
+        match message {
+            Message::Ping(ping) => {
+                if ping.num_pong_bytes > MAX_NUM_PONG_BYTES {
+                    return Ok(KeepaliveOutcome::Handled);
+                }
+
+                Ok(KeepaliveOutcome::Reply(Message::Pong(Pong {
+                    ignored: vec![0; ping.num_pong_bytes as usize],
+                })))
+            }
+            Message::Pong(pong) => {
+                // Unsolicited pongs are ignored.
+                if let Some((want, _)) = self.pending_pong {
+                    if pong.ignored.len() != want {
+                        return Err(ProtocolError::InvalidPongLength {
+                            want,
+                            got: pong.ignored.len(),
+                        });
+                    }
+
+                    self.pending_pong = None;
+                }
+
+                Ok(KeepaliveOutcome::Handled)
+            }
+            Message::UnknownEven { message_type, .. } => {
+                Err(ProtocolError::UnknownEvenMessage(message_type))
+            }
+            x => Ok(KeepaliveOutcome::Message(x)),
+        }
+    }
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60), Duration::from_secs(30))
+    }
+}
+
+/// A message received from the remote node, once handled by the keepalive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeepaliveOutcome {
+    /// The ping of the remote node, with the pong to reply with.
+    Reply(Message),
+
+    /// A ping or a pong, which requires no reply.
+    Handled,
+
+    /// Any other message.
+    Message(Message),
+}
+
+impl Communication {
+    /// Reads the next message from the remote node that is not a ping or a pong.
+    ///
+    /// Meanwhile, pings are sent at the interval of the keepalive and the pings of the remote
+    /// node are answered. Fails when the remote node does not respond to a ping in time,
+    /// responds with a pong of the wrong length, or sends a message of an unknown even type.
+    pub async fn read_message_with_keepalive(
+        &mut self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        keepalive: &mut Keepalive,
+    ) -> Result<Message, ProtocolError> {
+        loop {
+            let m = match time::timeout_at(keepalive.deadline(), self.read_message(stream)).await {
+                Ok(m) => m?,
+                Err(_) => {
+                    let ping = keepalive.on_deadline()?;
+                    self.send_message(stream, &ping.encode()?).await?;
+
+                    continue;
+                }
+            };
+
+            match keepalive.on_message(Message::decode(&m)?)? {
+                KeepaliveOutcome::Reply(pong) => self.send_message(stream, &pong.encode()?).await?,
+                KeepaliveOutcome::Handled => {}
+                KeepaliveOutcome::Message(x) => return Ok(x),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::protocol::communication::communications;
+
+    fn unknown_message() -> Message {
+        Message::UnknownOdd {
+            message_type: 32769,
+            payload: vec![0x01, 0x02],
+        }
+    }
+
+    #[tokio::test]
+    async fn it_answers_pings_with_pongs_of_the_requested_length() {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let mut keepalive = Keepalive::default();
+
+        let ping = Message::Ping(Ping {
+            num_pong_bytes: 5,
+            ignored: vec![0; 3],
+        });
+
+        for m in [ping, unknown_message()] {
+            let m = m.encode().unwrap();
+            remote.send_message(&mut remote_stream, &m).await.unwrap();
+        }
+
+        let message = local
+            .read_message_with_keepalive(&mut local_stream, &mut keepalive)
+            .await
+            .unwrap();
+        assert_eq! { message, unknown_message() };
+
+        let m = remote.read_message(&mut remote_stream).await.unwrap();
+        assert_eq! {
+            Message::decode(&m).unwrap(),
+            Message::Pong(Pong { ignored: vec![0; 5] })
+        };
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_ignores_pings_that_do_not_expect_a_pong() {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let mut keepalive = Keepalive::default();
+
+        let ping = Message::Ping(Ping {
+            num_pong_bytes: MAX_NUM_PONG_BYTES + 1,
+            ignored: vec![],
+        });
+
+        for m in [ping, unknown_message()] {
+            let m = m.encode().unwrap();
+            remote.send_message(&mut remote_stream, &m).await.unwrap();
+        }
+
+        let message = local
+            .read_message_with_keepalive(&mut local_stream, &mut keepalive)
+            .await
+            .unwrap();
+        assert_eq! { message, unknown_message() };
+
+        let result = time::timeout(
+            Duration::from_secs(1),
+            remote.read_message(&mut remote_stream),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_sends_pings_and_accepts_the_matching_pongs() {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let mut keepalive = Keepalive::new(Duration::from_secs(10), Duration::from_secs(5));
+
+        let remote_node = async {
+            // Two pings are answered before the remote node sends another message.
+            for _ in 0..2 {
+                let m = remote.read_message(&mut remote_stream).await.unwrap();
+
+                let Message::Ping(ping) = Message::decode(&m).unwrap() else {
+                    panic!("Expected a ping");
+                };
+                assert!(ping.num_pong_bytes <= MAX_NUM_PONG_BYTES);
+
+                let pong = Message::Pong(Pong {
+                    ignored: vec![0; ping.num_pong_bytes as usize],
+                });
+                remote
+                    .send_message(&mut remote_stream, &pong.encode().unwrap())
+                    .await
+                    .unwrap();
+            }
+
+            let m = unknown_message().encode().unwrap();
+            remote.send_message(&mut remote_stream, &m).await.unwrap();
+        };
+
+        let start = Instant::now();
+
+        let (message, _) = tokio::join!(
+            local.read_message_with_keepalive(&mut local_stream, &mut keepalive),
+            remote_node,
+        );
+
+        assert_eq! { message.unwrap(), unknown_message() };
+        assert_eq! { start.elapsed(), Duration::from_secs(20) };
+        assert!(keepalive.pending_pong.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_fails_when_the_pongs_stop_arriving() {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let mut keepalive = Keepalive::new(Duration::from_secs(10), Duration::from_secs(5));
+
+        let remote_node = async {
+            let m = remote.read_message(&mut remote_stream).await.unwrap();
+            assert!(matches!(Message::decode(&m).unwrap(), Message::Ping(_)));
+        };
+
+        let start = Instant::now();
+
+        let (result, _) = tokio::join!(
+            local.read_message_with_keepalive(&mut local_stream, &mut keepalive),
+            remote_node,
+        );
+
+        assert!(matches!(result, Err(ProtocolError::PongTimeout)));
+        assert_eq! { start.elapsed(), Duration::from_secs(15) };
+    }
+
+    #[tokio::test]
+    async fn it_fails_on_messages_of_an_unknown_even_type() {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let mut keepalive = Keepalive::default();
+
+        let m = Message::UnknownEven {
+            message_type: 0x8000,
+            payload: vec![0x01, 0x02],
+        };
+        remote
+            .send_message(&mut remote_stream, &m.encode().unwrap())
+            .await
+            .unwrap();
+
+        let result = local
+            .read_message_with_keepalive(&mut local_stream, &mut keepalive)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ProtocolError::UnknownEvenMessage(0x8000))
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn it_rejects_pongs_of_the_wrong_length() {
+        let (mut local, mut remote) = communications();
+        let (mut local_stream, mut remote_stream) = tokio::io::duplex(1024);
+
+        let mut keepalive = Keepalive::new(Duration::from_secs(10), Duration::from_secs(5));
+
+        let remote_node = async {
+            let m = remote.read_message(&mut remote_stream).await.unwrap();
+
+            let Message::Ping(ping) = Message::decode(&m).unwrap() else {
+                panic!("Expected a ping");
+            };
+
+            let pong = Message::Pong(Pong {
+                ignored: vec![0; ping.num_pong_bytes as usize ^ 1],
+            });
+            remote
+                .send_message(&mut remote_stream, &pong.encode().unwrap())
+                .await
+                .unwrap();
+
+            ping.num_pong_bytes as usize
+        };
+
+        let (result, want) = tokio::join!(
+            local.read_message_with_keepalive(&mut local_stream, &mut keepalive),
+            remote_node,
+        );
+
+        match result {
+            Err(ProtocolError::InvalidPongLength { want: w, got }) => {
+                assert_eq! { w, want };
+                assert_eq! { got, want ^ 1 };
+            }
+            _ => panic!("Expected an invalid pong length"),
+        }
+    }
+}
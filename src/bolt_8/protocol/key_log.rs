@@ -0,0 +1,154 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+/// The environment variable read for the path of the key log file,
+/// which is the one the Wireshark Lightning dissector reads as well.
+pub const KEY_LOG_ENV: &str = "LIGHTNINGKEYLOGFILE";
+
+/// Writes the keys of the encrypted sessions to a file, so that captured traffic
+/// can be decrypted offline by the Wireshark Lightning dissector.
+///
+/// Every key, including the ones derived by the key rotation, is written on its own line
+/// when it is first used, in both directions:
+///
+/// ```text
+/// <16-byte MAC> <32-byte key>
+/// ```
+///
+/// The MAC, which identifies the key, is the tag of the first encrypted length under the key,
+/// i.e. bytes 2 to 18 of the first packet. Both values are hex encoded.
+///
+/// The file allows anyone reading it to decrypt the sessions, so it must never be enabled
+/// outside of debugging.
+#[derive(Debug, Clone)]
+pub struct KeyLog {
+    /// The file shared by every session, which is locked while a line is written.
+    file: Arc<Mutex<File>>,
+}
+
+impl KeyLog {
+    /// Opens the key log file, which is created when it does not exist
+    /// and appended to otherwise.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+
+        // The key log file is only readable by its owner.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(options.open(path)?)),
+        })
+    }
+
+    /// Opens the key log file set by the `LIGHTNINGKEYLOGFILE` environment variable, if any.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        match std::env::var_os(KEY_LOG_ENV) {
+            Some(path) if !path.is_empty() => Self::open(Path::new(&path)).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    // Writes the line of the key identified by the MAC.
+    //
+    // Failing to write the key log does not affect the session, so the errors are ignored.
+    pub(super) fn log(&self, mac: &[u8; 16], key: &[u8; 32]) {
+        let line = format!("{} {}\n", hex::encode(mac), hex::encode(key));
+
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::{crypto::decrypt_with_ad, protocol::communication::communications};
+    use std::{fs, path::PathBuf};
+
+    fn key_log_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lightning-client-{name}-{}.keylog",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        path
+    }
+
+    // Returns the keys logged in the file, keyed by their MAC.
+    fn logged_keys(path: &Path) -> Vec<([u8; 16], [u8; 32])> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (mac, key) = line.split_once(' ').unwrap();
+
+                (
+                    hex::decode(mac).unwrap().try_into().unwrap(),
+                    hex::decode(key).unwrap().try_into().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_logs_every_key_when_it_is_first_used() {
+        let path = key_log_file("rotation");
+        let key_log = KeyLog::open(&path).unwrap();
+
+        let (mut local, mut remote) = communications();
+        local.set_key_log(key_log);
+
+        let (sk, rk) = (*local.writer.sk, *local.reader.rk);
+
+        // The sending key is rotated after the 500th message.
+        let sent: Vec<_> = (0..501)
+            .map(|_| local.writer.encrypt(b"hello").unwrap())
+            .collect();
+
+        let received = remote.writer.encrypt(b"world").unwrap();
+        local.reader.decrypt(&received).unwrap();
+
+        let keys = logged_keys(&path);
+        assert_eq! { keys.len(), 3 };
+
+        // Each key is identified by the MAC of the first packet it encrypts,
+        // and decrypts the length of that packet.
+        for ((mac, key), packet) in keys.iter().zip([&sent[0], &sent[500], &received]) {
+            assert_eq! { mac, &packet[2..18] };
+            assert_eq! { decrypt_with_ad(key, 0, &[], &packet[..18]).unwrap(), [0, 5] };
+        }
+
+        assert_eq! { keys[0].1, sk };
+        assert_eq! { keys[2].1, rk };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq! { mode & 0o777, 0o600 };
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_appends_to_an_existing_file() {
+        let path = key_log_file("append");
+
+        KeyLog::open(&path).unwrap().log(&[0x01; 16], &[0x02; 32]);
+        KeyLog::open(&path).unwrap().log(&[0x03; 16], &[0x04; 32]);
+
+        assert_eq! { logged_keys(&path), [([0x01; 16], [0x02; 32]), ([0x03; 16], [0x04; 32])] };
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+//! This module defines a mock remote node, which performs the responder side of the handshake
+//! and follows a script, to test the client against misbehaving nodes without a real one.
+//!
+//! It is exposed by the `mock-peer` feature, for the tests of the applications built on the client.
+
+use crate::{
+    bolt_1::message::{Message, Ping, Pong},
+    bolt_8::protocol::{
+        server::{Act0, Act2},
+        EncryptedReader, EncryptedWriter, ProtocolError, ServerProtocol,
+    },
+    signer::InMemorySigner,
+};
+use secp256k1::SecretKey;
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+/// The steps of a valid handshake, after which the script may send and read messages.
+pub const HANDSHAKE: [Step; 3] = [
+    Step::ReadAct1,
+    Step::SendAct2(Act2Message::Valid),
+    Step::ReadAct3,
+];
+
+/// A step of the script followed by the mock peer.
+#[derive(Debug, Clone)]
+pub enum Step {
+    /// Reads the Act-1 message of the client.
+    ReadAct1,
+
+    /// Sends the Act-2 message, which may be altered.
+    SendAct2(Act2Message),
+
+    /// Reads the Act-3 message of the client, which completes the handshake.
+    ReadAct3,
+
+    /// Sends the encrypted message.
+    Send(Message),
+
+    /// Sends the encrypted bytes, which need not be a valid message.
+    SendBytes(Vec<u8>),
+
+    /// Sends the encrypted bytes, with a tag that does not match them.
+    SendBadMac(Vec<u8>),
+
+    /// Reads the next message of the client.
+    Read,
+
+    /// Reads the next ping of the client, and answers with the requested number of bytes
+    /// plus the extra bytes.
+    AnswerPing { extra_bytes: usize },
+
+    /// Stops responding while keeping the connection open.
+    Stall,
+}
+
+/// The Act-2 message sent by the mock peer.
+#[derive(Debug, Clone, Copy)]
+pub enum Act2Message {
+    /// The message of the handshake.
+    Valid,
+
+    /// The message with an unknown handshake version.
+    BadVersion,
+
+    /// The message with an ephemeral key that is not a valid public key.
+    BadKey,
+
+    /// The message with a tag that does not match.
+    BadMac,
+
+    /// The given number of the first bytes of the message.
+    Truncated(usize),
+}
+
+// The state of the mock peer between the steps.
+enum State {
+    Act0(ServerProtocol<Act0<InMemorySigner>>),
+    Act2(ServerProtocol<Act2>),
+    Communication(EncryptedReader, EncryptedWriter),
+}
+
+/// A remote node that follows a script, instead of the protocol.
+///
+/// The connection is closed once the script is over, so a script that ends in the middle
+/// of an act disconnects the client.
+#[derive(Debug)]
+pub struct MockPeer {
+    /// The static secret key of the mock peer.
+    ls_sk: SecretKey,
+
+    /// The steps to follow.
+    script: Vec<Step>,
+}
+
+impl MockPeer {
+    /// Creates a mock peer with the static secret key and the script to follow.
+    pub fn new(ls_sk: SecretKey, script: impl IntoIterator<Item = Step>) -> Self {
+        Self {
+            ls_sk,
+            script: script.into_iter().collect(),
+        }
+    }
+
+    /// Runs the script on a new in-memory stream, and returns the other end of the stream.
+    pub fn spawn(
+        self,
+    ) -> (
+        DuplexStream,
+        JoinHandle<Result<Vec<Message>, ProtocolError>>,
+    ) {
+        let (client_stream, peer_stream) = tokio::io::duplex(1024);
+
+        (client_stream, tokio::spawn(self.run(peer_stream)))
+    }
+
+    /// Runs the script on the first connection accepted on a local TCP port,
+    /// and returns the address of the port.
+    pub async fn listen(self) -> (SocketAddr, JoinHandle<Result<Vec<Message>, ProtocolError>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let peer = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            self.run(stream).await
+        });
+
+        (address, peer)
+    }
+
+    /// Runs the script on the stream, and returns the messages read from the client.
+    ///
+    /// Fails when the client does not follow the protocol, or disconnects.
+    pub async fn run(
+        self,
+        mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    ) -> Result<Vec<Message>, ProtocolError> {
+        let mut state = State::Act0(ServerProtocol::new(self.ls_sk));
+        let mut received = Vec::new();
+
+        for step in self.script {
+            state = match (step, state) {
+                (Step::ReadAct1, State::Act0(server_proto)) => {
+                    let mut m = [0u8; 50];
+                    stream.read_exact(&mut m).await?;
+
+                    State::Act2(server_proto.process_inbound(&m)?.into_next_phase()?)
+                }
+                (Step::SendAct2(act_2), State::Act2(server_proto)) => {
+                    let mut m = server_proto.next_outbound();
+
+                    let len = match act_2 {
+                        Act2Message::Valid => m.len(),
+                        Act2Message::BadVersion => {
+                            m[0] = 1;
+                            m.len()
+                        }
+                        Act2Message::BadKey => {
+                            m[1] = 4;
+                            m.len()
+                        }
+                        Act2Message::BadMac => {
+                            m[49] ^= 1;
+                            m.len()
+                        }
+                        Act2Message::Truncated(len) => len,
+                    };
+
+                    stream.write_all(&m[..len]).await?;
+
+                    State::Act2(server_proto)
+                }
+                (Step::ReadAct3, State::Act2(server_proto)) => {
+                    let mut m = [0u8; 66];
+                    stream.read_exact(&mut m).await?;
+
+                    let (reader, writer) =
+                        server_proto.process_inbound(&m)?.into_next_phase().split();
+
+                    State::Communication(reader, writer)
+                }
+                (Step::Send(message), State::Communication(reader, mut writer)) => {
+                    writer.send_message(&mut stream, &message.encode()?).await?;
+
+                    State::Communication(reader, writer)
+                }
+                (Step::SendBytes(bytes), State::Communication(reader, mut writer)) => {
+                    writer.send_message(&mut stream, &bytes).await?;
+
+                    State::Communication(reader, writer)
+                }
+                (Step::SendBadMac(bytes), State::Communication(reader, mut writer)) => {
+                    let mut packet = writer.encrypt(&bytes)?;
+                    *packet.last_mut().unwrap() ^= 1;
+
+                    stream.write_all(&packet).await?;
+
+                    State::Communication(reader, writer)
+                }
+                (Step::Read, State::Communication(mut reader, writer)) => {
+                    let m = reader.read_message(&mut stream).await?;
+                    received.push(Message::decode(&m)?);
+
+                    State::Communication(reader, writer)
+                }
+                (
+                    Step::AnswerPing { extra_bytes },
+                    State::Communication(mut reader, mut writer),
+                ) => {
+                    let m = reader.read_message(&mut stream).await?;
+
+                    let ping = match Message::decode(&m)? {
+                        Message::Ping(x) => x,
+                        x => {
+                            return Err(ProtocolError::UnexpectedMessage {
+                                expected: Ping::TYPE,
+                                got: x.message_type(),
+                            })
+                        }
+                    };
+
+                    let pong = Message::Pong(Pong {
+                        ignored: vec![0; ping.num_pong_bytes as usize + extra_bytes],
+                    });
+                    writer.send_message(&mut stream, &pong.encode()?).await?;
+
+                    received.push(Message::Ping(ping));
+
+                    State::Communication(reader, writer)
+                }
+                (Step::Stall, _) => return std::future::pending().await,
+                (step, _) => panic!("The step {step:?} cannot be taken in this phase"),
+            };
+        }
+
+        Ok(received)
+    }
+}
@@ -1,7 +1,32 @@
 //! This module defines the BOLT-8 protocol.
+//!
+//! The handshake and the encryption of the messages are performed without IO,
+//! while the `async` feature adds the methods that drive them over tokio streams.
 
-mod client;
+pub mod client;
+#[cfg(feature = "async")]
+mod codec;
+mod communication;
 mod error;
+mod handshake;
+#[cfg(feature = "async")]
+mod init;
+#[cfg(feature = "async")]
+mod keepalive;
+mod key_log;
+#[cfg(any(all(test, feature = "async"), feature = "mock-peer"))]
+pub mod mock_peer;
+pub mod server;
 
 pub use self::client::ClientProtocol;
+#[cfg(feature = "async")]
+pub use self::codec::Bolt8Codec;
+pub use self::communication::{Communication, Decrypted, EncryptedReader, EncryptedWriter};
 pub use self::error::ProtocolError;
+pub use self::handshake::HandshakePhase;
+#[cfg(feature = "async")]
+pub use self::handshake::HandshakeTimeouts;
+#[cfg(feature = "async")]
+pub use self::keepalive::{Keepalive, KeepaliveOutcome};
+pub use self::key_log::{KeyLog, KEY_LOG_ENV};
+pub use self::server::ServerProtocol;
@@ -0,0 +1,57 @@
+use crate::bolt_8::crypto::{Secret, Sha256Digest};
+use secp256k1::PublicKey;
+
+/// Accumulates the state during the Act-0 of the handshake procedure.
+#[derive(Debug)]
+pub struct Act0<S> {
+    /// The signer that holds the static secret key of the local node.
+    pub(super) signer: S,
+
+    /// The chaining key.
+    pub(super) ck: Secret<[u8; 32]>,
+
+    /// The handshake hash.
+    pub(super) h: Sha256Digest,
+}
+
+impl<S> Act0<S> {
+    /// Initiates the Act-0 of the handshake procedure,
+    /// with the signer that holds the static secret key of the local node.
+    pub(super) fn new(ls_pk: PublicKey, signer: S) -> Self {
+        let mut h = Sha256Digest::new();
+
+        h.update(b"Noise_XK_secp256k1_ChaChaPoly_SHA256"); // Protocol name;
+
+        let ck = Secret::new(*h.as_bytes());
+
+        h.update(b"lightning"); // Prologue;
+
+        h.update(&ls_pk.serialize());
+
+        Self { signer, ck, h }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::InMemorySigner;
+    use hex_literal::hex;
+    use secp256k1::{SecretKey, SECP256K1};
+
+    #[test]
+    fn it_accumulates_the_correct_state() {
+        // Values used for testing are taken from BOLT-8 test vectors.
+        // See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+        let ls_sk = hex!("2121212121212121212121212121212121212121212121212121212121212121");
+        let ls_sk = SecretKey::from_slice(&ls_sk).unwrap();
+
+        let ls_pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        let act_0 = Act0::new(ls_pk, InMemorySigner::new(ls_sk));
+
+        assert_eq! { *act_0.ck, hex!("2640f52eebcd9e882958951c794250eedb28002c05d7dc2ea0f195406042caf1") };
+        assert_eq! { act_0.h.as_bytes(), &hex!("8401b3fdcaaa710b5405400536a3d5fd7792fe8e7fe29cd8b687216fe323ecbd") };
+    }
+}
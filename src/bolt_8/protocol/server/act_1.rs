@@ -0,0 +1,126 @@
+use crate::bolt_8::{
+    crypto::{decrypt_with_ad, hkdf, Secret, Sha256Digest},
+    protocol::{server::Act0, ProtocolError},
+};
+use secp256k1::PublicKey;
+
+/// Accumulates the state during the Act-1 of the handshake procedure.
+#[derive(Debug)]
+pub struct Act1 {
+    /// The ephemeral public key of the remote node.
+    pub(super) re_pk: PublicKey,
+
+    /// The chaining key.
+    pub(super) ck: Secret<[u8; 32]>,
+
+    /// The handshake hash.
+    pub(super) h: Sha256Digest,
+}
+
+impl Act1 {
+    /// Returns the ephemeral public key of the remote node carried by the message,
+    /// with which the signer performs the ECDH of the handshake phase.
+    pub(super) fn remote_ephemeral_key(rm: &[u8; 50]) -> Result<PublicKey, ProtocolError> {
+        let (v, re_pk) = (rm[0], &rm[1..34]);
+
+        if v != 0 {
+            return Err(ProtocolError::UnknownHandshakeVersion(v));
+        }
+
+        PublicKey::from_slice(re_pk).map_err(|e| ProtocolError::InvalidPublicKey {
+            hex: hex::encode(re_pk),
+            source: eyre::Report::new(e),
+        })
+    }
+
+    /// Initiates the Act-1 of the handshake procedure, with the result of the ECDH
+    /// between the ephemeral key of the remote node and the static key of the local node.
+    pub(super) fn new<S>(
+        act_0: Act0<S>,
+        rm: &[u8; 50],
+        es: Secret<[u8; 32]>,
+    ) -> Result<Self, ProtocolError> {
+        let Act0 {
+            signer: _,
+            ck,
+            mut h,
+        } = act_0;
+
+        let re_pk = Self::remote_ephemeral_key(rm)?;
+        let c = &rm[34..];
+
+        h.update(&re_pk.serialize());
+
+        let (ck, temp_k1) = hkdf(&ck, &es);
+
+        let _ = decrypt_with_ad(&temp_k1, 0, h.as_bytes(), c)?;
+
+        h.update(c);
+
+        Ok(Self { re_pk, ck, h })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::protocol::ServerProtocol;
+    use hex_literal::hex;
+    use secp256k1::SecretKey;
+
+    fn act_1(rm: &[u8; 50]) -> Result<Act1, ProtocolError> {
+        let ls_sk = hex!("2121212121212121212121212121212121212121212121212121212121212121");
+        let ls_sk = SecretKey::from_slice(&ls_sk).unwrap();
+
+        ServerProtocol::new(ls_sk)
+            .process_inbound(rm)
+            .map(|x| x.state)
+    }
+
+    #[test]
+    fn it_accumulates_the_correct_state() {
+        // Values used for testing are taken from BOLT-8 test vectors.
+        // See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+        let re_pk = hex!("036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f7");
+        let re_pk = PublicKey::from_slice(&re_pk).unwrap();
+
+        let rm = hex!("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+        let act_1 = act_1(&rm).unwrap();
+
+        assert_eq! { act_1.re_pk, re_pk };
+        assert_eq! { *act_1.ck, hex!("b61ec1191326fa240decc9564369dbb3ae2b34341d1e11ad64ed89f89180582f") };
+        assert_eq! { act_1.h.as_bytes(), &hex!("9d1ffbb639e7e20021d9259491dc7b160aab270fb1339ef135053f6f2cebe9ce") };
+    }
+
+    #[test]
+    fn it_rejects_a_bad_version() {
+        let rm = hex!("01036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+        assert!(matches!(
+            act_1(&rm),
+            Err(ProtocolError::UnknownHandshakeVersion(1))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_bad_key_serialization() {
+        let rm = hex!("00046360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+        assert!(matches!(
+            act_1(&rm),
+            Err(ProtocolError::InvalidPublicKey { .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_bad_mac() {
+        let rm = hex!("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6b");
+
+        assert!(matches!(
+            act_1(&rm),
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+}
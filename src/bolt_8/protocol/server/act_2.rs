@@ -0,0 +1,119 @@
+use crate::bolt_8::{
+    crypto::{ecdh, encrypt_with_ad, hkdf, Secret, Sha256Digest},
+    protocol::{server::Act1, ProtocolError},
+};
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+
+/// Accumulates the state during the Act-2 of the handshake procedure.
+#[derive(Debug)]
+pub struct Act2 {
+    /// The ephemeral public key of the local node.
+    pub(super) le_pk: PublicKey,
+
+    /// The ephemeral secret key of the local node.
+    pub(super) le_sk: Secret<SecretKey>,
+
+    /// The chaining key.
+    pub(super) ck: Secret<[u8; 32]>,
+
+    /// The intermediate key.
+    pub(super) temp_k2: Secret<[u8; 32]>,
+
+    /// The Poly1305 tag.
+    pub(super) c: Vec<u8>,
+
+    /// The handshake hash.
+    pub(super) h: Sha256Digest,
+}
+
+impl Act2 {
+    /// Initiates the Act-2 of the handshake procedure.
+    pub(super) fn new(act_1: Act1) -> Result<Self, ProtocolError> {
+        let le_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        Self::new_static(act_1, le_sk)
+    }
+
+    pub(super) fn new_static(act_1: Act1, le_sk: SecretKey) -> Result<Self, ProtocolError> {
+        let Act1 { re_pk, ck, mut h } = act_1;
+
+        let le_pk = PublicKey::from_secret_key(SECP256K1, &le_sk);
+
+        h.update(&le_pk.serialize());
+
+        let ee = ecdh(&re_pk, &le_sk);
+
+        let (ck, temp_k2) = hkdf(&ck, &ee);
+
+        let c = encrypt_with_ad(&temp_k2, 0, h.as_bytes(), b"")?;
+
+        h.update(&c);
+
+        Ok(Self {
+            le_pk,
+            le_sk: Secret::new(le_sk),
+            ck,
+            temp_k2,
+            c,
+            h,
+        })
+    }
+
+    // Returns the message to send to the remote node.
+    //
+    // The handshake message is exactly 50 bytes:
+    //     - 1 byte for the handshake version;
+    //     - 33 bytes for the compressed ephemeral public key of the responder;
+    //     - 16 bytes for the poly1305 tag;
+    pub(super) fn message(&self) -> [u8; 50] {
+        let mut m = [0u8; 50];
+
+        // Handshake version.
+        //
+        // A version of 0 indicates that no change is necessary,
+        // while a non-zero version indicate that the server has deviated from the protocol.
+        m[0] = 0;
+
+        m[1..34].copy_from_slice(&self.le_pk.serialize()[..33]);
+        m[34..].copy_from_slice(&self.c[..16]);
+
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::protocol::ServerProtocol;
+    use hex_literal::hex;
+
+    #[test]
+    fn it_accumulates_the_correct_state() {
+        // Values used for testing are taken from BOLT-8 test vectors.
+        // See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+        let ls_sk = hex!("2121212121212121212121212121212121212121212121212121212121212121");
+        let ls_sk = SecretKey::from_slice(&ls_sk).unwrap();
+
+        let le_sk = hex!("2222222222222222222222222222222222222222222222222222222222222222");
+        let le_sk = SecretKey::from_slice(&le_sk).unwrap();
+
+        let rm = hex!("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+        let act_1 = ServerProtocol::new(ls_sk)
+            .process_inbound(&rm)
+            .unwrap()
+            .state;
+
+        let act_2 = Act2::new_static(act_1, le_sk).unwrap();
+
+        assert_eq! { act_2.le_pk.serialize(), hex!("02466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f27") };
+        assert_eq! { *act_2.le_sk, le_sk };
+        assert_eq! { *act_2.ck, hex!("e89d31033a1b6bf68c07d22e08ea4d7884646c4b60a9528598ccb4ee2c8f56ba") };
+        assert_eq! { *act_2.temp_k2, hex!("908b166535c01a935cf1e130a5fe895ab4e6f3ef8855d87e9b7581c4ab663ddc") };
+        assert_eq! { act_2.c, hex!("6e2470b93aac583c9ef6eafca3f730ae") };
+        assert_eq! { act_2.h.as_bytes(), &hex!("90578e247e98674e661013da3c5c1ca6a8c8f48c90b485c0dfa1494e23d56d72") };
+
+        assert_eq! { act_2.message(), hex!("0002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae") };
+    }
+}
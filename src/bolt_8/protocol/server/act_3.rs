@@ -0,0 +1,183 @@
+use crate::bolt_8::{
+    crypto::{decrypt_with_ad, ecdh, hkdf, Secret},
+    protocol::{server::Act2, Communication, EncryptedReader, EncryptedWriter, ProtocolError},
+};
+use secp256k1::PublicKey;
+
+/// Accumulates the state during the Act-3 of the handshake procedure.
+#[derive(Debug)]
+pub struct Act3 {
+    /// The static public key of the remote node.
+    pub(super) rs_pk: PublicKey,
+
+    /// The sending encryption key.
+    pub(super) sk: Secret<[u8; 32]>,
+
+    /// The receiving decryption key.
+    pub(super) rk: Secret<[u8; 32]>,
+
+    /// The sending nonce.
+    pub(super) sn: u64,
+
+    /// The receiving nonce.
+    pub(super) rn: u64,
+
+    /// The sending chaining key.
+    pub(super) sck: Secret<[u8; 32]>,
+
+    /// The receiving chaining key.
+    pub(super) rck: Secret<[u8; 32]>,
+}
+
+impl Act3 {
+    /// Initiates the Act-3 of the handshake procedure.
+    pub(super) fn new(act_2: Act2, rm: &[u8; 66]) -> Result<Self, ProtocolError> {
+        let Act2 {
+            le_pk: _,
+            le_sk,
+            ck,
+            temp_k2,
+            c: _,
+            mut h,
+        } = act_2;
+
+        let (v, c, t) = (rm[0], &rm[1..50], &rm[50..]);
+
+        if v != 0 {
+            return Err(ProtocolError::UnknownHandshakeVersion(v));
+        }
+
+        let rs_pk = decrypt_with_ad(&temp_k2, 1, h.as_bytes(), c)?;
+
+        let rs_pk = PublicKey::from_slice(&rs_pk).map_err(|e| ProtocolError::InvalidPublicKey {
+            hex: hex::encode(&rs_pk),
+            source: eyre::Report::new(e),
+        })?;
+
+        h.update(c);
+
+        let se = ecdh(&rs_pk, &le_sk);
+
+        let (ck, temp_k3) = hkdf(&ck, &se);
+
+        let _ = decrypt_with_ad(&temp_k3, 0, h.as_bytes(), t)?;
+
+        // The responder derives the keys in the reverse order of the initiator.
+        let (rk, sk) = hkdf(&ck, b"");
+
+        Ok(Self {
+            rs_pk,
+            sk,
+            rk,
+            sn: 0,
+            rn: 0,
+            sck: ck.clone(),
+            rck: ck,
+        })
+    }
+}
+
+impl From<Act3> for Communication {
+    fn from(act_3: Act3) -> Self {
+        let Act3 {
+            rs_pk: _,
+            sk,
+            rk,
+            sn,
+            rn,
+            sck,
+            rck,
+        } = act_3;
+
+        Self {
+            reader: EncryptedReader::new(rk, rn, rck),
+            writer: EncryptedWriter::new(sk, sn, sck),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::protocol::ServerProtocol;
+    use hex_literal::hex;
+    use secp256k1::SecretKey;
+
+    fn act_2() -> Act2 {
+        let ls_sk = hex!("2121212121212121212121212121212121212121212121212121212121212121");
+        let ls_sk = SecretKey::from_slice(&ls_sk).unwrap();
+
+        let le_sk = hex!("2222222222222222222222222222222222222222222222222222222222222222");
+        let le_sk = SecretKey::from_slice(&le_sk).unwrap();
+
+        let rm = hex!("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a");
+
+        let act_1 = ServerProtocol::new(ls_sk)
+            .process_inbound(&rm)
+            .unwrap()
+            .state;
+
+        Act2::new_static(act_1, le_sk).unwrap()
+    }
+
+    #[test]
+    fn it_accumulates_the_correct_state() {
+        // Values used for testing are taken from BOLT-8 test vectors.
+        // See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+        let rs_pk = hex!("034f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa");
+        let rs_pk = PublicKey::from_slice(&rs_pk).unwrap();
+
+        let rm = hex!("00b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba");
+
+        let act_3 = Act3::new(act_2(), &rm).unwrap();
+
+        assert_eq! { act_3.rs_pk, rs_pk };
+        assert_eq! { *act_3.sk, hex!("bb9020b8965f4df047e07f955f3c4b88418984aadc5cdb35096b9ea8fa5c3442") };
+        assert_eq! { *act_3.rk, hex!("969ab31b4d288cedf6218839b27a3e2140827047f2c0f01bf5c04435d43511a9") };
+        assert_eq! { act_3.sn, 0 };
+        assert_eq! { act_3.rn, 0 };
+        assert_eq! { *act_3.sck, hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01") };
+        assert_eq! { *act_3.rck, hex!("919219dbb2920afa8db80f9a51787a840bcf111ed8d588caf9ab4be716e42b01") };
+    }
+
+    #[test]
+    fn it_rejects_a_bad_version() {
+        let rm = hex!("01b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba");
+
+        assert!(matches!(
+            Act3::new(act_2(), &rm),
+            Err(ProtocolError::UnknownHandshakeVersion(1))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_bad_ciphertext() {
+        let rm = hex!("00c9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba");
+
+        assert!(matches!(
+            Act3::new(act_2(), &rm),
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_bad_public_key() {
+        let rm = hex!("00bfe3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa2235536ad09a8ee351870c2bb7f78b754a26c6cef79a98d25139c856d7efd252c2ae73c");
+
+        assert!(matches!(
+            Act3::new(act_2(), &rm),
+            Err(ProtocolError::InvalidPublicKey { .. })
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_bad_mac() {
+        let rm = hex!("00b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139bb");
+
+        assert!(matches!(
+            Act3::new(act_2(), &rm),
+            Err(ProtocolError::CryptographyFailure { .. })
+        ));
+    }
+}
@@ -0,0 +1,456 @@
+//! This module defines the responder of the handshake, which accepts a remote node.
+
+mod act_0;
+mod act_1;
+mod act_2;
+mod act_3;
+
+pub use self::{act_0::Act0, act_1::Act1, act_2::Act2, act_3::Act3};
+#[cfg(feature = "async")]
+use crate::{
+    bolt_1::message::{Init, Message},
+    bolt_8::protocol::{HandshakePhase, HandshakeTimeouts, Keepalive},
+    signer::AsyncNodeSigner,
+};
+use crate::{
+    bolt_8::protocol::{Communication, EncryptedReader, EncryptedWriter, KeyLog, ProtocolError},
+    signer::{InMemorySigner, NodeSigner},
+};
+use secp256k1::{PublicKey, SecretKey};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Defines a step-by-step procedure for responding to a handshake
+/// and initiating encrypted communication with a remote node.
+#[derive(Debug)]
+pub struct ServerProtocol<T> {
+    state: T,
+}
+
+impl ServerProtocol<()> {
+    /// Creates a new session for a remote node to connect to,
+    /// with the static secret key of the local node.
+    pub fn new(ls_sk: SecretKey) -> ServerProtocol<Act0<InMemorySigner>> {
+        Self::with_signer(InMemorySigner::new(ls_sk))
+    }
+
+    /// Creates a new session for a remote node to connect to,
+    /// with the signer that holds the static secret key of the local node.
+    pub fn with_signer<S: NodeSigner>(signer: S) -> ServerProtocol<Act0<S>> {
+        ServerProtocol {
+            state: Act0::new(signer.node_id(), signer),
+        }
+    }
+
+    /// Creates a new session for a remote node to connect to,
+    /// with the asynchronous signer that holds the static secret key of the local node.
+    #[cfg(feature = "async")]
+    pub fn with_async_signer<S: AsyncNodeSigner>(signer: S) -> ServerProtocol<Act0<S>> {
+        ServerProtocol {
+            state: Act0::new(signer.node_id(), signer),
+        }
+    }
+}
+
+impl<S: NodeSigner> ServerProtocol<Act0<S>> {
+    /// Proceeds to the next handshake phase with the message received from the remote node.
+    pub fn process_inbound(
+        self,
+        message: &[u8; 50],
+    ) -> Result<ServerProtocol<Act1>, ProtocolError> {
+        let re_pk = Act1::remote_ephemeral_key(message)?;
+        let es = self.state.signer.ecdh(&re_pk)?;
+
+        Ok(ServerProtocol {
+            state: Act1::new(self.state, message, es)?,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncNodeSigner> ServerProtocol<Act0<S>> {
+    /// Proceeds to the next handshake phase, once the signer has performed its ECDH.
+    pub async fn into_next_phase(
+        self,
+        stream: &mut (impl AsyncRead + Unpin),
+    ) -> Result<ServerProtocol<Act1>, ProtocolError> {
+        let mut buf = [0u8; 50];
+        stream.read_exact(&mut buf).await?;
+
+        let re_pk = Act1::remote_ephemeral_key(&buf)?;
+        let es = self.state.signer.ecdh(&re_pk).await?;
+
+        Ok(ServerProtocol {
+            state: Act1::new(self.state, &buf, es)?,
+        })
+    }
+
+    /// Responds to the whole handshake of the remote node,
+    /// and returns the static public key of the remote node along with the communication.
+    ///
+    /// Fails with a timeout when an act or the whole handshake does not complete in time.
+    pub async fn accept_handshake(
+        self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        timeouts: &HandshakeTimeouts,
+    ) -> Result<(ServerProtocol<Communication>, PublicKey), ProtocolError> {
+        let deadline = timeouts.start();
+
+        let server_proto = timeouts
+            .run(HandshakePhase::Act1, deadline, self.into_next_phase(stream))
+            .await?;
+
+        let server_proto = server_proto.into_next_phase()?;
+
+        timeouts
+            .run(
+                HandshakePhase::Act2,
+                deadline,
+                server_proto.send_message(stream),
+            )
+            .await?;
+
+        let server_proto = timeouts
+            .run(
+                HandshakePhase::Act3,
+                deadline,
+                server_proto.into_next_phase(stream),
+            )
+            .await?;
+
+        let rs_pk = server_proto.remote_public_key();
+
+        Ok((server_proto.into_next_phase(), rs_pk))
+    }
+}
+
+impl ServerProtocol<Act1> {
+    /// Proceeds to the next handshake phase.
+    pub fn into_next_phase(self) -> Result<ServerProtocol<Act2>, ProtocolError> {
+        Ok(ServerProtocol {
+            state: Act2::new(self.state)?,
+        })
+    }
+
+    /// Proceeds to the next handshake phase with a fixed ephemeral key,
+    /// so that the fuzz targets reach the next phases deterministically.
+    #[cfg(feature = "fuzzing")]
+    pub fn into_next_phase_with_ephemeral_key(
+        self,
+        le_sk: SecretKey,
+    ) -> Result<ServerProtocol<Act2>, ProtocolError> {
+        Ok(ServerProtocol {
+            state: Act2::new_static(self.state, le_sk)?,
+        })
+    }
+}
+
+impl ServerProtocol<Act2> {
+    /// Returns the message to send to the remote node.
+    pub fn next_outbound(&self) -> [u8; 50] {
+        self.state.message()
+    }
+
+    /// Sends the message to the remote node.
+    #[cfg(feature = "async")]
+    pub async fn send_message(
+        &self,
+        stream: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<(), ProtocolError> {
+        stream.write_all(&self.next_outbound()).await?;
+
+        Ok(())
+    }
+
+    /// Proceeds to the next handshake phase with the message received from the remote node.
+    pub fn process_inbound(
+        self,
+        message: &[u8; 66],
+    ) -> Result<ServerProtocol<Act3>, ProtocolError> {
+        Ok(ServerProtocol {
+            state: Act3::new(self.state, message)?,
+        })
+    }
+
+    /// Proceeds to the next handshake phase.
+    #[cfg(feature = "async")]
+    pub async fn into_next_phase(
+        self,
+        stream: &mut (impl AsyncRead + Unpin),
+    ) -> Result<ServerProtocol<Act3>, ProtocolError> {
+        let mut buf = [0u8; 66];
+        stream.read_exact(&mut buf).await?;
+
+        self.process_inbound(&buf)
+    }
+}
+
+impl ServerProtocol<Act3> {
+    /// Returns the static public key of the remote node.
+    pub fn remote_public_key(&self) -> PublicKey {
+        self.state.rs_pk
+    }
+
+    /// Proceeds to the communication phase.
+    pub fn into_next_phase(self) -> ServerProtocol<Communication> {
+        ServerProtocol {
+            state: Communication::from(self.state),
+        }
+    }
+}
+
+impl ServerProtocol<Communication> {
+    /// Reads a message from the remote node.
+    #[cfg(feature = "async")]
+    pub async fn read_message(
+        &mut self,
+        stream: &mut (impl AsyncRead + Unpin),
+    ) -> Result<Vec<u8>, ProtocolError> {
+        self.state.read_message(stream).await
+    }
+
+    /// Sends a message to the remote node.
+    #[cfg(feature = "async")]
+    pub async fn send_message(
+        &mut self,
+        stream: &mut (impl AsyncWrite + Unpin),
+        message: &[u8],
+    ) -> Result<(), ProtocolError> {
+        self.state.send_message(stream, message).await
+    }
+
+    /// Sends the init message of the local node and reads the init message of the remote node.
+    #[cfg(feature = "async")]
+    pub async fn exchange_init(
+        &mut self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        init: &Init,
+        chain_hash: &[u8; 32],
+    ) -> Result<Init, ProtocolError> {
+        self.state.exchange_init(stream, init, chain_hash).await
+    }
+
+    /// Reads the next message from the remote node that is not a ping or a pong.
+    #[cfg(feature = "async")]
+    pub async fn read_message_with_keepalive(
+        &mut self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        keepalive: &mut Keepalive,
+    ) -> Result<Message, ProtocolError> {
+        self.state
+            .read_message_with_keepalive(stream, keepalive)
+            .await
+    }
+
+    /// Splits the communication into its receiving and sending halves.
+    pub fn split(self) -> (EncryptedReader, EncryptedWriter) {
+        self.state.split()
+    }
+
+    /// Writes the keys of the session to the key log, to decrypt captured traffic offline.
+    pub fn set_key_log(&mut self, key_log: KeyLog) {
+        self.state.set_key_log(key_log);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "async")]
+    use crate::{
+        bolt_8::crypto::Secret,
+        signer::{AsyncNodeSigner, SignerError},
+    };
+    use crate::{
+        bolt_8::{
+            crypto::read_after_drop,
+            protocol::{ClientProtocol, Decrypted},
+        },
+        signer::{InMemorySigner, NodeSigner},
+    };
+    use secp256k1::SECP256K1;
+    #[cfg(feature = "async")]
+    use std::sync::Arc;
+
+    // A signer that rejects every ECDH.
+    #[cfg(feature = "async")]
+    struct RejectingSigner(PublicKey);
+
+    #[cfg(feature = "async")]
+    impl AsyncNodeSigner for RejectingSigner {
+        fn node_id(&self) -> PublicKey {
+            self.0
+        }
+
+        async fn ecdh(&self, _: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+            Err(SignerError::Rejected(1))
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_performs_the_handshake_with_a_client() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let (mut client_stream, mut server_stream) = tokio::io::duplex(1024);
+
+        let client = async {
+            let client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &ls_sk));
+            let client_proto = client_proto
+                .into_next_phase(InMemorySigner::new(rs_sk))
+                .unwrap();
+            client_proto.send_message(&mut client_stream).await.unwrap();
+            let client_proto = client_proto
+                .into_next_phase(&mut client_stream)
+                .await
+                .unwrap();
+            let client_proto = client_proto.into_next_phase().await.unwrap();
+            client_proto.send_message(&mut client_stream).await.unwrap();
+            let mut client_proto = client_proto.into_next_phase();
+
+            client_proto
+                .send_message(&mut client_stream, b"hello")
+                .await
+                .unwrap();
+
+            client_proto.read_message(&mut client_stream).await.unwrap()
+        };
+
+        let server = async {
+            let server_proto = ServerProtocol::new(ls_sk);
+            let server_proto = server_proto
+                .into_next_phase(&mut server_stream)
+                .await
+                .unwrap();
+            let server_proto = server_proto.into_next_phase().unwrap();
+            server_proto.send_message(&mut server_stream).await.unwrap();
+            let server_proto = server_proto
+                .into_next_phase(&mut server_stream)
+                .await
+                .unwrap();
+
+            assert_eq! { server_proto.remote_public_key(), PublicKey::from_secret_key(SECP256K1, &rs_sk) };
+
+            let mut server_proto = server_proto.into_next_phase();
+
+            let message = server_proto.read_message(&mut server_stream).await.unwrap();
+
+            server_proto
+                .send_message(&mut server_stream, b"world")
+                .await
+                .unwrap();
+
+            message
+        };
+
+        let (client_message, server_message) = tokio::join!(client, server);
+
+        assert_eq! { server_message, b"hello" };
+        assert_eq! { client_message, b"world" };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_accepts_the_handshake_with_an_asynchronous_signer() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let (mut client_stream, mut server_stream) = tokio::io::duplex(1024);
+        let timeouts = HandshakeTimeouts::default();
+
+        let signer = Arc::new(InMemorySigner::new(ls_sk));
+        let client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &ls_sk));
+
+        let (client, server) = tokio::join!(
+            client_proto.perform_handshake(
+                &mut client_stream,
+                InMemorySigner::new(rs_sk),
+                &timeouts
+            ),
+            ServerProtocol::with_async_signer(signer)
+                .accept_handshake(&mut server_stream, &timeouts),
+        );
+
+        assert!(client.is_ok());
+        assert_eq! { server.unwrap().1, PublicKey::from_secret_key(SECP256K1, &rs_sk) };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_fails_when_the_signer_rejects_the_ecdh() {
+        let ls_pk = PublicKey::from_secret_key(
+            SECP256K1,
+            &SecretKey::new(&mut secp256k1::rand::thread_rng()),
+        );
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let (mut client_stream, mut server_stream) = tokio::io::duplex(1024);
+        let timeouts = HandshakeTimeouts::default();
+
+        // The stream of the server is dropped once it fails, which disconnects the client.
+        let server = async move {
+            ServerProtocol::with_async_signer(RejectingSigner(ls_pk))
+                .accept_handshake(&mut server_stream, &timeouts)
+                .await
+                .map(|_| ())
+        };
+
+        let (client, server) = tokio::join!(
+            ClientProtocol::new(ls_pk).perform_handshake(
+                &mut client_stream,
+                InMemorySigner::new(rs_sk),
+                &timeouts
+            ),
+            server,
+        );
+
+        assert!(matches!(server, Err(ProtocolError::SignerFailure { .. })));
+        assert!(matches!(client, Err(ProtocolError::IoError { .. })));
+    }
+
+    #[test]
+    fn it_performs_the_handshake_without_io() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let signer = InMemorySigner::new(rs_sk);
+        let client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &ls_sk));
+        let client_proto = client_proto.into_next_phase(&signer).unwrap();
+
+        let server_proto = ServerProtocol::new(ls_sk);
+        let server_proto = server_proto
+            .process_inbound(&client_proto.next_outbound())
+            .unwrap();
+        let server_proto = server_proto.into_next_phase().unwrap();
+
+        let client_proto = client_proto
+            .process_inbound(&server_proto.next_outbound())
+            .unwrap();
+        let se = NodeSigner::ecdh(&signer, &client_proto.remote_ephemeral_key()).unwrap();
+        let client_proto = client_proto.process_ecdh(se).unwrap();
+
+        let server_proto = server_proto
+            .process_inbound(&client_proto.next_outbound())
+            .unwrap();
+
+        assert_eq! { server_proto.remote_public_key(), PublicKey::from_secret_key(SECP256K1, &rs_sk) };
+
+        let (_, mut client_writer) = client_proto.into_next_phase().split();
+        let (mut server_reader, _) = server_proto.into_next_phase().state.split();
+
+        let packet = client_writer.encrypt(b"hello").unwrap();
+
+        assert_eq! {
+            server_reader.decrypt(&packet).unwrap(),
+            Decrypted::Message(b"hello".to_vec())
+        };
+    }
+
+    #[test]
+    fn it_zeroizes_the_secrets_on_drop() {
+        let ls_sk = SecretKey::from_slice(&[0x21; 32]).unwrap();
+
+        assert_eq! { read_after_drop(ServerProtocol::new(ls_sk), |x| &*x.state.ck), [0; 32] };
+    }
+}
@@ -0,0 +1,28 @@
+use std::fmt;
+
+/// A context in which a feature vector is presented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// The `init` message.
+    Init,
+
+    /// The `node_announcement` message.
+    Node,
+
+    /// The `channel_announcement` message.
+    Channel,
+
+    /// The BOLT-11 invoice.
+    Invoice,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Init => write!(f, "init"),
+            Self::Node => write!(f, "node_announcement"),
+            Self::Channel => write!(f, "channel_announcement"),
+            Self::Invoice => write!(f, "invoice"),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#[derive(Debug, thiserror::Error)]
+pub enum FeatureError {
+    #[error("The '{feature}' feature requires the '{dependency}' feature")]
+    MissingDependency {
+        feature: &'static str,
+        dependency: &'static str,
+    },
+
+    #[error("The '{feature}' feature is not allowed in the '{context}' context")]
+    InvalidContext {
+        feature: &'static str,
+        context: String,
+    },
+}
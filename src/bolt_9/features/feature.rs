@@ -0,0 +1,134 @@
+use crate::bolt_9::features::Context;
+
+use Context::{Init as I, Invoice as B, Node as N};
+
+/// A feature assigned by BOLT-9.
+///
+/// Every feature owns a pair of bits, where the even one marks the feature
+/// as required and the odd one marks the feature as optional.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    /// The name of the feature.
+    pub name: &'static str,
+
+    /// The even bit of the feature.
+    pub bit: usize,
+
+    /// The contexts in which the feature may be presented.
+    pub contexts: &'static [Context],
+
+    /// The features that must also be set when the feature is set.
+    pub dependencies: &'static [Feature],
+}
+
+impl Feature {
+    pub const OPTION_DATA_LOSS_PROTECT: Self =
+        Self::new("option_data_loss_protect", 0, &[I, N], &[]);
+    pub const OPTION_UPFRONT_SHUTDOWN_SCRIPT: Self =
+        Self::new("option_upfront_shutdown_script", 4, &[I, N], &[]);
+    pub const GOSSIP_QUERIES: Self = Self::new("gossip_queries", 6, &[I, N], &[]);
+    pub const VAR_ONION_OPTIN: Self = Self::new("var_onion_optin", 8, &[I, N, B], &[]);
+    pub const GOSSIP_QUERIES_EX: Self =
+        Self::new("gossip_queries_ex", 10, &[I, N], &[Self::GOSSIP_QUERIES]);
+    pub const OPTION_STATIC_REMOTEKEY: Self =
+        Self::new("option_static_remotekey", 12, &[I, N], &[]);
+    pub const PAYMENT_SECRET: Self =
+        Self::new("payment_secret", 14, &[I, N, B], &[Self::VAR_ONION_OPTIN]);
+    pub const BASIC_MPP: Self = Self::new("basic_mpp", 16, &[I, N, B], &[Self::PAYMENT_SECRET]);
+    pub const OPTION_SUPPORT_LARGE_CHANNEL: Self =
+        Self::new("option_support_large_channel", 18, &[I, N], &[]);
+    pub const OPTION_ANCHOR_OUTPUTS: Self = Self::new(
+        "option_anchor_outputs",
+        20,
+        &[I, N],
+        &[Self::OPTION_STATIC_REMOTEKEY],
+    );
+    pub const OPTION_ANCHORS_ZERO_FEE_HTLC_TX: Self = Self::new(
+        "option_anchors_zero_fee_htlc_tx",
+        22,
+        &[I, N],
+        &[Self::OPTION_STATIC_REMOTEKEY],
+    );
+    pub const OPTION_ROUTE_BLINDING: Self = Self::new("option_route_blinding", 24, &[I, N, B], &[]);
+    pub const OPTION_SHUTDOWN_ANYSEGWIT: Self =
+        Self::new("option_shutdown_anysegwit", 26, &[I, N], &[]);
+    pub const OPTION_DUAL_FUND: Self = Self::new("option_dual_fund", 28, &[I, N], &[]);
+    pub const OPTION_QUIESCE: Self = Self::new("option_quiesce", 34, &[I, N], &[]);
+    pub const OPTION_ONION_MESSAGES: Self = Self::new("option_onion_messages", 38, &[I, N], &[]);
+    pub const OPTION_CHANNEL_TYPE: Self = Self::new("option_channel_type", 44, &[I, N], &[]);
+    pub const OPTION_SCID_ALIAS: Self = Self::new("option_scid_alias", 46, &[I, N], &[]);
+    pub const OPTION_PAYMENT_METADATA: Self = Self::new("option_payment_metadata", 48, &[B], &[]);
+    pub const OPTION_ZEROCONF: Self =
+        Self::new("option_zeroconf", 50, &[I, N], &[Self::OPTION_SCID_ALIAS]);
+
+    /// All the features known by the local node, ordered by bit.
+    pub const ALL: [Self; 20] = [
+        Self::OPTION_DATA_LOSS_PROTECT,
+        Self::OPTION_UPFRONT_SHUTDOWN_SCRIPT,
+        Self::GOSSIP_QUERIES,
+        Self::VAR_ONION_OPTIN,
+        Self::GOSSIP_QUERIES_EX,
+        Self::OPTION_STATIC_REMOTEKEY,
+        Self::PAYMENT_SECRET,
+        Self::BASIC_MPP,
+        Self::OPTION_SUPPORT_LARGE_CHANNEL,
+        Self::OPTION_ANCHOR_OUTPUTS,
+        Self::OPTION_ANCHORS_ZERO_FEE_HTLC_TX,
+        Self::OPTION_ROUTE_BLINDING,
+        Self::OPTION_SHUTDOWN_ANYSEGWIT,
+        Self::OPTION_DUAL_FUND,
+        Self::OPTION_QUIESCE,
+        Self::OPTION_ONION_MESSAGES,
+        Self::OPTION_CHANNEL_TYPE,
+        Self::OPTION_SCID_ALIAS,
+        Self::OPTION_PAYMENT_METADATA,
+        Self::OPTION_ZEROCONF,
+    ];
+
+    const fn new(
+        name: &'static str,
+        bit: usize,
+        contexts: &'static [Context],
+        dependencies: &'static [Feature],
+    ) -> Self {
+        Self {
+            name,
+            bit,
+            contexts,
+            dependencies,
+        }
+    }
+
+    /// Returns the known feature owning the bit passed, either even or odd.
+    pub fn from_bit(bit: usize) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.bit == bit & !1)
+    }
+
+    /// Returns the bit marking the feature as required.
+    pub fn required_bit(&self) -> usize {
+        self.bit
+    }
+
+    /// Returns the bit marking the feature as optional.
+    pub fn optional_bit(&self) -> usize {
+        self.bit + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_the_feature_by_either_bit() {
+        assert_eq! { Feature::from_bit(14), Some(Feature::PAYMENT_SECRET) };
+        assert_eq! { Feature::from_bit(15), Some(Feature::PAYMENT_SECRET) };
+        assert_eq! { Feature::from_bit(30), None };
+    }
+
+    #[test]
+    fn it_lists_the_features_ordered_by_bit() {
+        assert!(Feature::ALL.windows(2).all(|x| x[0].bit < x[1].bit));
+        assert!(Feature::ALL.iter().all(|x| x.bit % 2 == 0));
+    }
+}
@@ -0,0 +1,271 @@
+//! This module defines the feature bits assigned by the BOLT-9 protocol.
+//!
+//! Features are presented as a big-endian bit vector, where the bits are numbered
+//! from the least-significant bit of the last byte.
+
+mod context;
+mod error;
+mod feature;
+
+pub use self::context::Context;
+pub use self::error::FeatureError;
+pub use self::feature::Feature;
+
+use std::fmt;
+
+/// A feature vector.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Features {
+    /// The bytes of the vector in the little-endian order, so that the bit `n` is in the byte `n / 8`.
+    le: Vec<u8>,
+}
+
+impl Features {
+    /// Creates a feature vector from its big-endian representation.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut le = bytes.to_vec();
+        le.reverse();
+
+        Self::from_le(le)
+    }
+
+    /// Returns the big-endian representation of the feature vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.le.clone();
+        bytes.reverse();
+
+        bytes
+    }
+
+    /// Sets the bit passed.
+    pub fn set(&mut self, bit: usize) {
+        if self.le.len() <= bit / 8 {
+            self.le.resize(bit / 8 + 1, 0);
+        }
+
+        self.le[bit / 8] |= 1 << (bit % 8);
+    }
+
+    /// Returns whether the bit passed is set.
+    pub fn is_set(&self, bit: usize) -> bool {
+        self.le
+            .get(bit / 8)
+            .map_or(false, |x| x & (1 << (bit % 8)) != 0)
+    }
+
+    /// Returns whether the feature is set as either required or optional.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.requires(feature) || self.is_set(feature.optional_bit())
+    }
+
+    /// Returns whether the feature is set as required.
+    pub fn requires(&self, feature: Feature) -> bool {
+        self.is_set(feature.required_bit())
+    }
+
+    /// Returns the set bits in the ascending order.
+    pub fn bits(&self) -> Vec<usize> {
+        (0..self.le.len() * 8).filter(|x| self.is_set(*x)).collect()
+    }
+
+    /// Returns the union of both feature vectors.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut features = self.clone();
+
+        for bit in other.bits() {
+            features.set(bit);
+        }
+
+        features
+    }
+
+    /// Returns the set required bits that do not belong to a known feature.
+    pub fn unknown_required_bits(&self) -> Vec<usize> {
+        self.bits()
+            .into_iter()
+            .filter(|x| x % 2 == 0 && Feature::from_bit(*x).is_none())
+            .collect()
+    }
+
+    /// Checks that all the dependencies of the set features are also set.
+    ///
+    /// Dependencies are transitive, so checking the direct dependencies
+    /// of every set feature covers them all.
+    pub fn validate_dependencies(&self) -> Result<(), FeatureError> {
+        for feature in self.known() {
+            if let Some(dependency) = feature.dependencies.iter().find(|x| !self.supports(**x)) {
+                return Err(FeatureError::MissingDependency {
+                    feature: feature.name,
+                    dependency: dependency.name,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that all the set features may be presented in the context passed.
+    pub fn validate_context(&self, context: Context) -> Result<(), FeatureError> {
+        if let Some(feature) = self.known().find(|x| !x.contexts.contains(&context)) {
+            return Err(FeatureError::InvalidContext {
+                feature: feature.name,
+                context: context.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    // Returns the known features that are set.
+    fn known(&self) -> impl Iterator<Item = Feature> + '_ {
+        Feature::ALL.into_iter().filter(|x| self.supports(*x))
+    }
+
+    // Creates a feature vector without the trailing zero bytes,
+    // so that equal vectors have the same representation.
+    fn from_le(mut le: Vec<u8>) -> Self {
+        while le.last() == Some(&0) {
+            le.pop();
+        }
+
+        Self { le }
+    }
+}
+
+impl fmt::Display for Features {
+    /// Lists the features by name, followed by whether they are required or optional.
+    ///
+    /// Unknown features are listed by their bit.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = Vec::new();
+
+        for bit in self.bits() {
+            // The required bit has already been listed for features set as both required and optional.
+            if bit % 2 == 1 && self.is_set(bit - 1) {
+                continue;
+            }
+
+            let kind = if bit % 2 == 0 { "required" } else { "optional" };
+
+            match Feature::from_bit(bit) {
+                Some(x) => names.push(format!("{} ({kind})", x.name)),
+                None => names.push(format!("unknown_{bit} ({kind})")),
+            }
+        }
+
+        if names.is_empty() {
+            return write!(f, "none");
+        }
+
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+impl fmt::Debug for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Features({})", hex::encode(self.to_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn it_converts_from_and_to_bytes() {
+        let features = Features::from_bytes(&hex!("0000a088288a6981"));
+
+        assert_eq! { features.to_bytes(), hex!("a088288a6981") };
+        assert_eq! { features.bits(), vec![0, 7, 8, 11, 13, 14, 17, 19, 23, 27, 29, 35, 39, 45, 47] };
+    }
+
+    #[test]
+    fn it_sets_the_bits() {
+        let mut features = Features::default();
+
+        features.set(Feature::VAR_ONION_OPTIN.required_bit());
+        features.set(Feature::PAYMENT_SECRET.optional_bit());
+
+        assert_eq! { features.to_bytes(), hex!("8100") };
+        assert!(features.requires(Feature::VAR_ONION_OPTIN));
+        assert!(features.supports(Feature::PAYMENT_SECRET));
+        assert!(!features.requires(Feature::PAYMENT_SECRET));
+        assert!(!features.supports(Feature::BASIC_MPP));
+    }
+
+    #[test]
+    fn it_returns_the_union() {
+        let x = Features::from_bytes(&hex!("2200"));
+        let y = Features::from_bytes(&hex!("080281"));
+
+        assert_eq! { x.union(&y).bits(), vec![0, 7, 9, 13, 19] };
+        assert_eq! { x.union(&y), y.union(&x) };
+    }
+
+    #[test]
+    fn it_returns_the_unknown_required_bits() {
+        // Bit 31 is unknown but optional, while bit 0 is known.
+        let features = Features::from_bytes(&hex!("c0000001"));
+
+        assert_eq! { features.unknown_required_bits(), vec![30] };
+    }
+
+    #[test]
+    fn it_validates_the_dependencies() {
+        let mut features = Features::default();
+
+        features.set(Feature::BASIC_MPP.optional_bit());
+
+        assert!(matches!(
+            features.validate_dependencies(),
+            Err(FeatureError::MissingDependency {
+                feature: "basic_mpp",
+                dependency: "payment_secret"
+            })
+        ));
+
+        features.set(Feature::PAYMENT_SECRET.required_bit());
+
+        assert!(matches!(
+            features.validate_dependencies(),
+            Err(FeatureError::MissingDependency {
+                feature: "payment_secret",
+                dependency: "var_onion_optin"
+            })
+        ));
+
+        features.set(Feature::VAR_ONION_OPTIN.optional_bit());
+
+        assert!(features.validate_dependencies().is_ok());
+    }
+
+    #[test]
+    fn it_validates_the_context() {
+        let mut features = Features::default();
+
+        features.set(Feature::BASIC_MPP.optional_bit());
+
+        assert!(features.validate_context(Context::Init).is_ok());
+        assert!(features.validate_context(Context::Invoice).is_ok());
+        assert!(matches!(
+            features.validate_context(Context::Channel),
+            Err(FeatureError::InvalidContext {
+                feature: "basic_mpp",
+                ..
+            })
+        ));
+
+        features.set(Feature::OPTION_PAYMENT_METADATA.optional_bit());
+
+        assert!(features.validate_context(Context::Init).is_err());
+    }
+
+    #[test]
+    fn it_displays_the_features() {
+        let features = Features::from_bytes(&hex!("010000000203"));
+
+        assert_eq! { features.to_string(), "option_data_loss_protect (required), var_onion_optin (optional), unknown_40 (required)" };
+        assert_eq! { Features::default().to_string(), "none" };
+    }
+}
@@ -0,0 +1,5 @@
+//! This module is an implementation of the BOLT-9 protocol.
+//!
+//! Spec: <https://github.com/lightning/bolts/blob/master/09-features.md>
+
+pub mod features;
@@ -0,0 +1,31 @@
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityError {
+    #[error("IO error")]
+    IoError { source: eyre::Report },
+
+    #[error("The key file is not valid: {0}")]
+    InvalidKeyFile(String),
+
+    #[error("The key file is encrypted, but no passphrase was provided")]
+    MissingPassphrase,
+
+    #[error("The key file is not encrypted, but a passphrase was provided")]
+    UnencryptedKeyFile,
+
+    #[error("The key file could not be decrypted, the passphrase may be wrong")]
+    DecryptionFailed { source: eyre::Report },
+
+    #[error("The key file could not be encrypted")]
+    EncryptionFailed { source: eyre::Report },
+
+    #[error("The key file does not contain a valid secret key")]
+    InvalidSecretKey { source: eyre::Report },
+}
+
+impl From<std::io::Error> for IdentityError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError {
+            source: eyre::Report::new(e),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! This module defines the persistent identity of the local node.
+//!
+//! The static secret key of the node is stored in a key file, either in plain
+//! or encrypted with a key derived from a passphrase.
+
+mod error;
+
+pub use self::error::IdentityError;
+
+use crate::bolt_8::crypto::{decrypt_with_ad, encrypt_with_ad, Secret};
+use secp256k1::{rand::RngCore, PublicKey, SecretKey, SECP256K1};
+use sha2::Sha256;
+use std::{fs, io::Write, path::Path};
+use zeroize::Zeroizing;
+
+/// The version of a key file that contains the secret key in plain.
+const PLAIN_VERSION: u8 = 0;
+
+/// The version of a key file that contains the secret key encrypted with a passphrase.
+const ENCRYPTED_VERSION: u8 = 1;
+
+/// The number of PBKDF2 iterations used to derive the encryption key from a passphrase.
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// The lowest number of PBKDF2 iterations accepted in a key file.
+///
+/// The number of iterations is read before the key file is authenticated,
+/// so it is bounded on both sides to keep a tampered key file from stalling the loading.
+const ITERATIONS_MIN: u32 = 1_000;
+
+/// The highest number of PBKDF2 iterations accepted in a key file.
+const ITERATIONS_MAX: u32 = 10 * PBKDF2_ITERATIONS;
+
+/// The identity of the local node, defined by its static secret key.
+#[derive(Debug)]
+pub struct NodeIdentity {
+    /// The static secret key of the local node.
+    ls_sk: Secret<SecretKey>,
+}
+
+impl NodeIdentity {
+    /// Generates a new identity from a random secret key.
+    pub fn generate() -> Self {
+        Self {
+            ls_sk: Secret::new(SecretKey::new(&mut secp256k1::rand::thread_rng())),
+        }
+    }
+
+    /// Loads the identity from the key file, or generates and saves a new one
+    /// when the key file does not exist yet.
+    pub fn load_or_generate(path: &Path, passphrase: Option<&str>) -> Result<Self, IdentityError> {
+        if path.exists() {
+            return Self::load(path, passphrase);
+        }
+
+        let identity = Self::generate();
+        identity.save(path, passphrase)?;
+
+        Ok(identity)
+    }
+
+    /// Loads the identity from the key file.
+    pub fn load(path: &Path, passphrase: Option<&str>) -> Result<Self, IdentityError> {
+        Self::decode(&fs::read(path)?, passphrase)
+    }
+
+    /// Saves the identity to a new key file, encrypted when a passphrase is provided.
+    ///
+    /// Fails when the key file already exists, so that an identity is never overwritten.
+    pub fn save(&self, path: &Path, passphrase: Option<&str>) -> Result<(), IdentityError> {
+        let bytes = match passphrase {
+            Some(passphrase) => {
+                Zeroizing::new(self.encode_encrypted(passphrase, PBKDF2_ITERATIONS)?)
+            }
+            None => self.encode_plain(),
+        };
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        // The key file is only readable by its owner.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        options.open(path)?.write_all(&bytes)?;
+
+        Ok(())
+    }
+
+    /// Returns the static secret key of the local node.
+    pub fn secret_key(&self) -> SecretKey {
+        *self.ls_sk
+    }
+
+    /// Returns the node id, which is the static public key of the local node.
+    pub fn node_id(&self) -> PublicKey {
+        PublicKey::from_secret_key(SECP256K1, &self.ls_sk)
+    }
+
+    // Returns the plain key file:
+    //     - 1 byte for the version;
+    //     - 32 bytes for the secret key;
+    fn encode_plain(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(vec![PLAIN_VERSION]);
+        bytes.extend_from_slice(&*Secret::new(self.ls_sk.secret_bytes()));
+
+        bytes
+    }
+
+    // Returns the encrypted key file:
+    //     - 1 byte for the version;
+    //     - 4 bytes for the big-endian number of PBKDF2 iterations;
+    //     - 16 bytes for the PBKDF2 salt;
+    //     - 32 bytes for the encrypted secret key followed by its 16 bytes tag;
+    //
+    // The header is authenticated as the associated data of the encryption. Since the salt
+    // is random, every encryption uses a distinct key, which allows a zero nonce.
+    fn encode_encrypted(
+        &self,
+        passphrase: &str,
+        iterations: u32,
+    ) -> Result<Vec<u8>, IdentityError> {
+        let mut salt = [0u8; 16];
+        secp256k1::rand::thread_rng().fill_bytes(&mut salt);
+
+        let mut bytes = vec![ENCRYPTED_VERSION];
+        bytes.extend_from_slice(&iterations.to_be_bytes());
+        bytes.extend_from_slice(&salt);
+
+        let key = derive_key(passphrase, &salt, iterations);
+
+        let c = encrypt_with_ad(&key, 0, &bytes, &*Secret::new(self.ls_sk.secret_bytes()))
+            .map_err(|e| IdentityError::EncryptionFailed {
+                source: eyre::Report::new(e),
+            })?;
+
+        bytes.extend_from_slice(&c);
+
+        Ok(bytes)
+    }
+
+    // Parses the key file, decrypting it with the passphrase when it is encrypted.
+    //
+    // A passphrase is only accepted for an encrypted key file, so that a key file
+    // believed to be encrypted is never loaded in plain.
+    fn decode(bytes: &[u8], passphrase: Option<&str>) -> Result<Self, IdentityError> {
+        let sk = match bytes.first() {
+            Some(&PLAIN_VERSION) => {
+                if bytes.len() != 33 {
+                    return Err(IdentityError::InvalidKeyFile(format!(
+                        "want 33 bytes, got {}",
+                        bytes.len()
+                    )));
+                }
+
+                if passphrase.is_some() {
+                    return Err(IdentityError::UnencryptedKeyFile);
+                }
+
+                Zeroizing::new(bytes[1..].to_vec())
+            }
+            Some(&ENCRYPTED_VERSION) => {
+                if bytes.len() != 69 {
+                    return Err(IdentityError::InvalidKeyFile(format!(
+                        "want 69 bytes, got {}",
+                        bytes.len()
+                    )));
+                }
+
+                let passphrase = passphrase.ok_or(IdentityError::MissingPassphrase)?;
+
+                let (header, c) = bytes.split_at(21);
+                let iterations = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+
+                if !(ITERATIONS_MIN..=ITERATIONS_MAX).contains(&iterations) {
+                    return Err(IdentityError::InvalidKeyFile(format!(
+                        "want {ITERATIONS_MIN} to {ITERATIONS_MAX} iterations, got {iterations}"
+                    )));
+                }
+
+                let key = derive_key(passphrase, &header[5..], iterations);
+
+                Zeroizing::new(decrypt_with_ad(&key, 0, header, c).map_err(|e| {
+                    IdentityError::DecryptionFailed {
+                        source: eyre::Report::new(e),
+                    }
+                })?)
+            }
+            Some(v) => {
+                return Err(IdentityError::InvalidKeyFile(format!(
+                    "unknown version '{v}'"
+                )))
+            }
+            None => return Err(IdentityError::InvalidKeyFile("empty file".to_owned())),
+        };
+
+        let ls_sk = SecretKey::from_slice(&sk).map_err(|e| IdentityError::InvalidSecretKey {
+            source: eyre::Report::new(e),
+        })?;
+
+        Ok(Self {
+            ls_sk: Secret::new(ls_sk),
+        })
+    }
+}
+
+// Derives the encryption key of a key file from the passphrase with PBKDF2-HMAC-SHA256.
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Secret<[u8; 32]> {
+    let mut key = Secret::new([0u8; 32]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut *key);
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::crypto::read_after_drop;
+
+    // A low number of iterations keeps the tests fast.
+    const ITERATIONS: u32 = 1000;
+
+    // Returns a path in the temporary directory that does not exist yet.
+    fn key_file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lightning-client-{name}-{}.key",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        path
+    }
+
+    #[test]
+    fn it_generates_and_reloads_the_same_identity() {
+        let path = key_file("plain");
+
+        let identity = NodeIdentity::load_or_generate(&path, None).unwrap();
+        let reloaded = NodeIdentity::load_or_generate(&path, None).unwrap();
+
+        assert_eq! { reloaded.node_id(), identity.node_id() };
+        assert_eq! { fs::read(&path).unwrap().len(), 33 };
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq! { mode & 0o777, 0o600 };
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_refuses_to_overwrite_a_key_file() {
+        let path = key_file("overwrite");
+
+        NodeIdentity::generate().save(&path, None).unwrap();
+        let result = NodeIdentity::generate().save(&path, None);
+
+        assert!(matches!(result, Err(IdentityError::IoError { .. })));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_decrypts_the_key_file_with_the_passphrase() {
+        let identity = NodeIdentity::generate();
+
+        let bytes = identity.encode_encrypted("passphrase", ITERATIONS).unwrap();
+        assert_eq! { bytes.len(), 69 };
+        assert_eq! { bytes[..5], [ENCRYPTED_VERSION, 0, 0, 0x03, 0xe8] };
+
+        let decoded = NodeIdentity::decode(&bytes, Some("passphrase")).unwrap();
+        assert_eq! { decoded.secret_key(), identity.secret_key() };
+    }
+
+    #[test]
+    fn it_rejects_a_wrong_or_missing_passphrase() {
+        let bytes = NodeIdentity::generate()
+            .encode_encrypted("passphrase", ITERATIONS)
+            .unwrap();
+
+        assert!(matches!(
+            NodeIdentity::decode(&bytes, Some("wrong")),
+            Err(IdentityError::DecryptionFailed { .. })
+        ));
+        assert!(matches!(
+            NodeIdentity::decode(&bytes, None),
+            Err(IdentityError::MissingPassphrase)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_passphrase_for_a_plain_key_file() {
+        let bytes = NodeIdentity::generate().encode_plain();
+
+        assert!(matches!(
+            NodeIdentity::decode(&bytes, Some("passphrase")),
+            Err(IdentityError::UnencryptedKeyFile)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_a_number_of_iterations_out_of_bounds() {
+        let identity = NodeIdentity::generate();
+
+        for iterations in [0, ITERATIONS_MIN - 1, ITERATIONS_MAX + 1, u32::MAX] {
+            let mut bytes = identity.encode_encrypted("passphrase", ITERATIONS).unwrap();
+            bytes[1..5].copy_from_slice(&iterations.to_be_bytes());
+
+            // The key file is rejected before any key is derived from the passphrase.
+            assert!(matches!(
+                NodeIdentity::decode(&bytes, Some("passphrase")),
+                Err(IdentityError::InvalidKeyFile(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_tampered_header() {
+        let mut bytes = NodeIdentity::generate()
+            .encode_encrypted("passphrase", ITERATIONS)
+            .unwrap();
+
+        // The salt is authenticated along with the secret key.
+        bytes[5] ^= 1;
+
+        assert!(matches!(
+            NodeIdentity::decode(&bytes, Some("passphrase")),
+            Err(IdentityError::DecryptionFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn it_zeroizes_and_redacts_the_secret_key() {
+        assert_eq! { read_after_drop(NodeIdentity::generate(), |x| &*x.ls_sk).secret_bytes(), [1; 32] };
+
+        let identity = NodeIdentity::generate();
+
+        assert_eq! { format!("{identity:?}"), "NodeIdentity { ls_sk: Secret([REDACTED]) }" };
+    }
+
+    #[test]
+    fn it_rejects_invalid_key_files() {
+        for bytes in [
+            vec![],
+            vec![PLAIN_VERSION; 32],
+            vec![ENCRYPTED_VERSION; 68],
+            vec![2; 33],
+        ] {
+            assert!(matches!(
+                NodeIdentity::decode(&bytes, None),
+                Err(IdentityError::InvalidKeyFile(_))
+            ));
+        }
+
+        // The secret key must be within the order of the curve.
+        let mut bytes = vec![PLAIN_VERSION];
+        bytes.extend_from_slice(&[0xff; 32]);
+
+        assert!(matches!(
+            NodeIdentity::decode(&bytes, None),
+            Err(IdentityError::InvalidSecretKey { .. })
+        ));
+    }
+}
@@ -0,0 +1,28 @@
+//! A Rust implementation of the Lightning Network Protocol.
+//!
+//! The BOLT-8 handshake and transport live in [`bolt_8::protocol`], built on the primitives
+//! of [`bolt_8::crypto`], while the static key of the local node is held by a [`signer`].
+//!
+//! The crate has the following features:
+//!
+//! - `async` _(default)_: drives the protocol over tokio streams, and adds the handshake
+//!   timeouts, the keepalive, the codec, the SOCKS5 proxy, the remote signer
+//!   and the [`peer`] manager.
+//! - `blocking`: drives the client over `std::io` streams, without a runtime.
+//! - `cli` _(default)_: builds the command line client.
+//! - `mock-peer`: exposes a mock remote node that follows a script, to test the applications
+//!   built on the client.
+//! - `fuzzing`: exposes the hooks of the fuzz targets, and is internal to this repository.
+//!
+//! Without any feature, the handshake and the encryption are performed without IO.
+
+pub mod bolt_1;
+pub mod bolt_7;
+pub mod bolt_8;
+pub mod bolt_9;
+pub mod identity;
+#[cfg(feature = "async")]
+pub mod peer;
+pub mod signer;
+#[cfg(feature = "async")]
+pub mod socks5;
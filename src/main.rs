@@ -1,30 +1,133 @@
-mod bolt_8;
-
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use color_eyre::eyre;
+use lightning_client::{
+    bolt_1::{
+        message::{Init, Message, Ping},
+        network::Network,
+    },
+    bolt_7::address::NodeAddress,
+    bolt_8::protocol::{
+        ClientProtocol, HandshakeTimeouts, Keepalive, KeyLog, ServerProtocol, KEY_LOG_ENV,
+    },
+    identity::NodeIdentity,
+    signer::InMemorySigner,
+    socks5::{Socks5Error, Socks5Proxy},
+};
 use secp256k1::{PublicKey, SecretKey};
-use std::process::ExitCode;
+use std::{net::SocketAddr, path::PathBuf, process::ExitCode};
 use tokio::{
-    net::TcpStream,
+    net::{TcpListener, TcpStream},
     time::{timeout, Duration},
 };
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    /// The address of the remote node in the following form: <public_key>@<ip>:<port>
+    /// The address of the remote node in the following form: <public_key>@<host>[:<port>]
     ///
-    /// Note: Any public node should work.
+    /// Note: The host is an IPv4 address, an IPv6 address in brackets or a hostname,
+    ///       and the port defaults to 9735.
+    ///       Any public node should work.
     ///       Public nodes can be found at https://1ml.com/
-    #[arg(short, long)]
-    node_address: String,
+    #[arg(short, long, required = true)]
+    node_address: Option<NodeAddress>,
+
+    /// The SOCKS5 proxy to connect through in the following form: [<username>:<password>@]<ip>:<port>
+    ///
+    /// Note: The proxy is required to reach Tor onion services, e.g. 127.0.0.1:9050.
+    ///       With Tor, distinct credentials isolate the connections on distinct circuits.
+    #[arg(long)]
+    proxy: Option<Socks5Proxy>,
+
+    /// The network the local node operates on: bitcoin, testnet, signet or regtest
+    #[arg(long, global = true, default_value = "bitcoin")]
+    network: Network,
+
+    /// The file that stores the secret key of the local node, created on the first run
+    ///
+    /// Note: Without a key file, a new identity is generated on every run.
+    #[arg(long, global = true)]
+    key_file: Option<PathBuf>,
+
+    /// The passphrase used to encrypt the key file
+    #[arg(
+        long,
+        global = true,
+        env = "LIGHTNING_KEY_PASSPHRASE",
+        hide_env_values = true
+    )]
+    key_passphrase: Option<String>,
+
+    /// The file the session keys are appended to, to decrypt captured traffic with Wireshark
+    ///
+    /// Note: Anyone reading the file can decrypt the sessions, only use it for debugging.
+    #[arg(long, global = true, env = KEY_LOG_ENV)]
+    keylog_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Accepts inbound connections from remote nodes.
+    Listen {
+        /// The address to listen on in the following form: <ip>:<port>
+        #[arg(short, long, default_value = "127.0.0.1:9735")]
+        address: String,
+    },
 }
 
 #[tokio::main]
 async fn main() -> ExitCode {
     let args = Args::parse();
 
-    if let Err(e) = perform_handshake(args).await {
+    let identity = match args.key_file {
+        Some(path) => NodeIdentity::load_or_generate(&path, args.key_passphrase.as_deref())
+            .map_err(|e| eyre::eyre!("Unable to load the key file {}: {e}", path.display())),
+        None => Ok(NodeIdentity::generate()),
+    };
+
+    let key_log = match &args.keylog_file {
+        Some(path) => KeyLog::open(path)
+            .map(Some)
+            .map_err(|e| eyre::eyre!("Unable to open the key log file {}: {e}", path.display())),
+        None => Ok(None),
+    };
+
+    let result = match (identity, key_log) {
+        (Ok(identity), Ok(key_log)) => {
+            println!("Node id: {}\n", hex::encode(identity.node_id().serialize()));
+
+            let ls_sk = identity.secret_key();
+
+            match (args.command, args.node_address) {
+                (Some(Command::Listen { address }), _) => {
+                    listen(&address, ls_sk, args.network, key_log).await
+                }
+                (None, Some(node_address)) => {
+                    perform_handshake(
+                        &node_address,
+                        args.proxy.as_ref(),
+                        ls_sk,
+                        args.network,
+                        key_log,
+                    )
+                    .await
+                }
+                (None, None) => unreachable!("The node address is required without a subcommand"),
+            }
+        }
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    };
+
+    if let Err(e) = result {
         println!("{e}");
         return ExitCode::FAILURE;
     }
@@ -32,59 +135,184 @@ async fn main() -> ExitCode {
     ExitCode::SUCCESS
 }
 
-async fn perform_handshake(args: Args) -> Result<(), eyre::Report> {
-    let (rs_pk, address) = args.node_address.split_once('@').ok_or_else(|| {
-        eyre::eyre!("Invalid node address. Expected format: <public_key>@<ip>:<port>")
-    })?;
+async fn perform_handshake(
+    node_address: &NodeAddress,
+    proxy: Option<&Socks5Proxy>,
+    ls_sk: SecretKey,
+    network: Network,
+    key_log: Option<KeyLog>,
+) -> Result<(), eyre::Report> {
+    let address = &node_address.address;
+
+    let mut stream = timeout(Duration::from_secs(10), address.connect(proxy))
+        .await
+        .map_err(|_e| eyre::eyre!("Unable to connect to the remote node at {address}."))?
+        .map_err(|e| match e {
+            Socks5Error::ProxyRequired => eyre::eyre!("{e}, see --proxy"),
+            e => eyre::eyre!("Unable to connect to the remote node at {address}: {e}"),
+        })?;
+
+    let mut client_proto = ClientProtocol::new(node_address.public_key)
+        .perform_handshake(
+            &mut stream,
+            InMemorySigner::new(ls_sk),
+            &HandshakeTimeouts::default(),
+        )
+        .await
+        .map_err(|e| eyre::eyre!("Failed to perform handshake: {e}"))?;
 
-    let rs_pk = hex::decode(rs_pk)
-        .map_err(|_e| eyre::eyre!("The provided node public key is not valid."))?;
+    println!("Handshake completed!\n");
 
-    let rs_pk = PublicKey::from_slice(&rs_pk)
-        .map_err(|_e| eyre::eyre!("The provided node public key is not valid."))?;
+    if let Some(key_log) = key_log {
+        client_proto.set_key_log(key_log);
+    }
 
-    let mut stream = timeout(Duration::from_secs(10), TcpStream::connect(address))
+    let remote_init = client_proto
+        .exchange_init(&mut stream, &local_init(network), &network.chain_hash())
         .await
-        .map_err(|_e| eyre::eyre!("Unable to connect to the remote node."))?
-        .map_err(|e| eyre::eyre!("Unable to connect to the remote node: {e}"))?;
+        .map_err(|e| eyre::eyre!("Failed to exchange init messages with the remote node: {e}"))?;
 
-    let client_proto = bolt_8::protocol::ClientProtocol::new(rs_pk);
+    println!("Successfully exchanged the init messages with the remote node!\n");
+    print_init(&remote_init);
 
-    let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+    let (mut reader, mut writer) = client_proto.split();
+    let (mut read_half, mut write_half) = stream.into_split();
 
-    let client_proto = client_proto
-        .into_next_phase(ls_sk)
-        .map_err(|e| eyre::eyre!("Failed to perform handshake: {e}"))?;
+    // The remote node may send other messages before responding to the ping,
+    // so they are read on a separate task while the ping is sent.
+    let pong = tokio::spawn(timeout(Duration::from_secs(10), async move {
+        loop {
+            let message = reader
+                .read_message(&mut read_half)
+                .await
+                .map_err(|e| eyre::eyre!("Failed to read message from the remote node: {e}"))?;
 
-    client_proto
-        .send_message(&mut stream)
+            if let Message::Pong(_) = Message::decode(&message)? {
+                return Ok::<_, eyre::Report>(());
+            }
+        }
+    }));
+
+    let ping = Message::Ping(Ping {
+        num_pong_bytes: 4,
+        ignored: vec![],
+    });
+
+    writer
+        .send_message(&mut write_half, &ping.encode()?)
         .await
-        .map_err(|e| eyre::eyre!("Failed to send hanshake message to remote node: {e}"))?;
+        .map_err(|e| eyre::eyre!("Failed to send ping message to the remote node: {e}"))?;
+
+    pong.await?
+        .map_err(|_e| eyre::eyre!("The remote node did not respond to the ping message."))??;
 
-    let client_proto = client_proto
-        .into_next_phase(&mut stream)
+    println!("\nSuccessfully received the pong message from the remote node!");
+
+    Ok(())
+}
+
+async fn listen(
+    address: &str,
+    ls_sk: SecretKey,
+    network: Network,
+    key_log: Option<KeyLog>,
+) -> Result<(), eyre::Report> {
+    let listener = TcpListener::bind(address)
         .await
-        .map_err(|e| eyre::eyre!("Failed to perform handshake: {e}"))?;
+        .map_err(|e| eyre::eyre!("Unable to listen on {address}: {e}"))?;
 
-    let client_proto = client_proto
-        .into_next_phase()
-        .map_err(|e| eyre::eyre!("Failed to perform handshake: {e}"))?;
+    let local_address = listener
+        .local_addr()
+        .map_err(|e| eyre::eyre!("Unable to listen on {address}: {e}"))?;
+
+    let ls_pk = PublicKey::from_secret_key(secp256k1::SECP256K1, &ls_sk);
 
-    client_proto
-        .send_message(&mut stream)
+    println!(
+        "Listening for inbound connections on: {}@{local_address}\n",
+        hex::encode(ls_pk.serialize())
+    );
+
+    loop {
+        let (stream, remote_address) = match listener.accept().await {
+            Ok(x) => x,
+            Err(e) => {
+                println!("Failed to accept an inbound connection: {e}");
+                continue;
+            }
+        };
+
+        let key_log = key_log.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = accept_handshake(stream, remote_address, ls_sk, network, key_log).await
+            {
+                println!("[{remote_address}] {e}");
+            }
+        });
+    }
+}
+
+async fn accept_handshake(
+    mut stream: TcpStream,
+    remote_address: SocketAddr,
+    ls_sk: SecretKey,
+    network: Network,
+    key_log: Option<KeyLog>,
+) -> Result<(), eyre::Report> {
+    let (mut server_proto, rs_pk) = ServerProtocol::new(ls_sk)
+        .accept_handshake(&mut stream, &HandshakeTimeouts::default())
         .await
-        .map_err(|e| eyre::eyre!("Failed to send hanshake message to remote node: {e}"))?;
+        .map_err(|e| eyre::eyre!("Failed to perform handshake: {e}"))?;
 
-    let mut client_proto = client_proto.into_next_phase();
+    println!(
+        "[{remote_address}] Handshake completed with: {}",
+        hex::encode(rs_pk.serialize())
+    );
 
-    let message = client_proto
-        .read_message(&mut stream)
+    if let Some(key_log) = key_log {
+        server_proto.set_key_log(key_log);
+    }
+
+    let remote_init = server_proto
+        .exchange_init(&mut stream, &local_init(network), &network.chain_hash())
         .await
-        .map_err(|e| eyre::eyre!("Failed to read init message from the remote node: {e}"))?;
+        .map_err(|e| eyre::eyre!("Failed to exchange init messages with the remote node: {e}"))?;
 
-    println!("Handshake completed!\n");
-    println!("Successfully read and decrypted the init message from the remote node!\n");
-    println!("Decrypted message (hex): {}", hex::encode(message));
+    println!(
+        "[{remote_address}] Remote features: {}",
+        remote_init.combined_features()
+    );
 
-    Ok(())
+    let mut keepalive = Keepalive::default();
+
+    loop {
+        let message = server_proto
+            .read_message_with_keepalive(&mut stream, &mut keepalive)
+            .await
+            .map_err(|e| eyre::eyre!("Connection closed: {e}"))?;
+
+        println!("[{remote_address}] Decoded message: {message:?}");
+    }
+}
+
+// Returns the init message of the local node, which only lists the network it operates on.
+fn local_init(network: Network) -> Init {
+    let mut init = Init::default();
+    init.set_networks(&[network.chain_hash()]);
+
+    init
+}
+
+fn print_init(init: &Init) {
+    println!("Remote features: {}", init.combined_features());
+
+    match init.networks() {
+        Ok(Some(networks)) => {
+            for chain_hash in networks {
+                println!("Remote network: {}", hex::encode(chain_hash));
+            }
+        }
+        Ok(None) => println!("Remote network: not specified"),
+        Err(e) => println!("Remote network: {e}"),
+    }
 }
@@ -0,0 +1,69 @@
+use secp256k1::rand::{thread_rng, Rng};
+use std::time::Duration;
+
+/// Spaces the attempts to reconnect to a remote node, doubling the delay after every failure.
+///
+/// Every delay is picked at random between half and the whole of the exponential delay,
+/// so that the nodes dropped at the same time do not all reconnect at the same time.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// The delay before the first attempt.
+    initial: Duration,
+
+    /// The delay the exponential delay is capped at.
+    max: Duration,
+}
+
+impl Backoff {
+    /// Creates a backoff starting at the initial delay and capped at the maximum delay.
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+
+    /// Returns the delay before the attempt, counted from zero since the last success.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max);
+
+        thread_rng().gen_range(delay / 2..=delay)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(300))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_doubles_the_delay_up_to_the_maximum() {
+        let backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10));
+
+        for (attempt, want) in [(0, 1), (1, 2), (2, 4), (3, 8), (4, 10), (40, 10)] {
+            let want = Duration::from_secs(want);
+
+            for _ in 0..100 {
+                let delay = backoff.delay(attempt);
+                assert!(
+                    want / 2 <= delay && delay <= want,
+                    "attempt {attempt}: {delay:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_spreads_the_delays() {
+        let backoff = Backoff::default();
+
+        let delays: Vec<_> = (0..100).map(|_| backoff.delay(3)).collect();
+
+        assert!(delays.iter().any(|x| *x != delays[0]));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{bolt_8::protocol::ProtocolError, socks5::Socks5Error};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PeerError {
+    #[error("IO error")]
+    IoError { source: eyre::Report },
+
+    #[error("Tor onion services can only be reached through a proxy")]
+    ProxyRequired,
+
+    #[error("The proxy has failed")]
+    ProxyFailure { source: eyre::Report },
+
+    #[error("The session with the remote node has failed")]
+    SessionFailure { source: eyre::Report },
+
+    #[error("The remote node did not respond in time")]
+    Timeout,
+
+    #[error("The node '{0}' is not known")]
+    UnknownPeer(String),
+
+    #[error("The node '{0}' is not connected")]
+    NotConnected(String),
+
+    #[error("The node '{0}' has too many messages waiting to be sent")]
+    OutboxFull(String),
+
+    #[error("The connection to the node '{0}' was cancelled")]
+    Cancelled(String),
+}
+
+impl From<std::io::Error> for PeerError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError {
+            source: eyre::Report::new(e),
+        }
+    }
+}
+
+impl From<Socks5Error> for PeerError {
+    fn from(e: Socks5Error) -> Self {
+        match e {
+            Socks5Error::ProxyRequired => Self::ProxyRequired,
+            e => Self::ProxyFailure {
+                source: eyre::Report::new(e),
+            },
+        }
+    }
+}
+
+impl From<ProtocolError> for PeerError {
+    fn from(e: ProtocolError) -> Self {
+        Self::SessionFailure {
+            source: eyre::Report::new(e),
+        }
+    }
+}
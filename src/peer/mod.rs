@@ -0,0 +1,1167 @@
+//! This module defines the peer manager, which keeps the sessions with many remote nodes alive
+//! and reconnects to them when their session drops.
+
+mod backoff;
+mod error;
+
+pub use self::backoff::Backoff;
+pub use self::error::PeerError;
+
+use crate::{
+    bolt_1::message::{Init, Message},
+    bolt_7::address::{Address, NodeAddress},
+    bolt_8::protocol::{
+        ClientProtocol, EncryptedReader, EncryptedWriter, HandshakeTimeouts, Keepalive,
+        KeepaliveOutcome, ProtocolError, ServerProtocol,
+    },
+    signer::AsyncNodeSigner,
+    socks5::Socks5Proxy,
+};
+use secp256k1::PublicKey;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+use tokio::{
+    net::{tcp::OwnedWriteHalf, TcpStream},
+    sync::mpsc::{self, error::TrySendError, Permit},
+    task::{AbortHandle, JoinHandle},
+    time::{self, Duration},
+};
+
+/// The time a remote node has to accept the TCP connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The time a remote node has to send its init message, once the handshake is completed.
+const INIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of messages that may wait to be sent to a remote node,
+/// beyond which sending more fails until the remote node reads them.
+pub const OUTBOX_CAPACITY: usize = 64;
+
+/// The number of events that may wait to be received, beyond which the sessions stop reading
+/// from their remote node until the events are received.
+pub const EVENTS_CAPACITY: usize = 256;
+
+/// The side that opened the connection of a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The local node connected to the remote node.
+    Outbound,
+
+    /// The remote node connected to the local node.
+    Inbound,
+}
+
+/// An event of the sessions kept by the peer manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// A session was opened with a node that was not connected.
+    Connected { node_id: PublicKey, init: Init },
+
+    /// A message that is not a ping or a pong was received from a node.
+    Message {
+        node_id: PublicKey,
+        message: Message,
+    },
+
+    /// The session with a node was closed.
+    Disconnected { node_id: PublicKey },
+}
+
+/// A remote node known to the peer manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerInfo {
+    /// The node id of the remote node.
+    pub node_id: PublicKey,
+
+    /// The address the node is reconnected to, if it was connected to with `connect`.
+    pub address: Option<Address>,
+
+    /// The direction of the session, which is missing while the node is connected
+    /// or reconnected to.
+    pub direction: Option<Direction>,
+}
+
+/// The state of a remote node.
+#[derive(Debug)]
+struct Peer {
+    /// The address the node is reconnected to, if it was connected to with `connect`.
+    address: Option<Address>,
+
+    /// The session with the node, while it is connected.
+    session: Option<Session>,
+
+    /// The task reconnecting to the node, while it is not connected.
+    reconnect: Option<JoinHandle<()>>,
+
+    /// The connection opened by `connect`, until it is opened.
+    connecting: Option<Connecting>,
+}
+
+/// A connection opened by `connect`, on its own task so that `disconnect` can cancel it.
+#[derive(Debug)]
+struct Connecting {
+    /// The id of the connection, which tells it apart from the connections that replaced it.
+    id: u64,
+
+    /// The task opening the connection.
+    task: AbortHandle,
+}
+
+/// A session with a remote node, which is run by its own task.
+#[derive(Debug)]
+struct Session {
+    /// The id of the session, which tells it apart from the sessions that replaced it.
+    id: u64,
+
+    /// The side that opened the connection.
+    direction: Direction,
+
+    /// The messages to send to the remote node.
+    outbox: mpsc::Sender<Vec<u8>>,
+
+    /// The task running the session.
+    task: JoinHandle<()>,
+}
+
+/// The state shared by the peer manager and the tasks it runs.
+#[derive(Debug)]
+struct Inner<S> {
+    /// The signer that holds the static secret key of the local node.
+    signer: S,
+
+    /// The init message sent to every remote node.
+    init: Init,
+
+    /// The chain the remote nodes must operate on.
+    chain_hash: [u8; 32],
+
+    /// The backoff of the reconnections.
+    backoff: Backoff,
+
+    /// The proxy the connections are opened through, if any.
+    proxy: Option<Socks5Proxy>,
+
+    /// The remote nodes, keyed by node id.
+    peers: Mutex<HashMap<PublicKey, Peer>>,
+
+    /// The sender of the events of the sessions.
+    events: mpsc::Sender<PeerEvent>,
+
+    /// The id of the next session or connection.
+    next_session_id: AtomicU64,
+}
+
+/// Keeps the sessions with many remote nodes, keyed by node id.
+///
+/// The nodes connected to with `connect` are reconnected to with the backoff whenever their
+/// session drops, until `disconnect` is called, while the nodes that connected to the local node
+/// are forgotten once their session drops.
+///
+/// When two nodes connect to each other at the same time, both keep the session opened by the node
+/// with the lowest node id, and close the other one.
+///
+/// The sessions are closed when the peer manager is dropped.
+#[derive(Debug)]
+pub struct PeerManager<S> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S: AsyncNodeSigner + 'static> PeerManager<S> {
+    /// Creates a peer manager for the local node, whose static secret key is held by the signer,
+    /// which sends the init message to every remote node and requires them to operate on the chain.
+    ///
+    /// Returns the receiver of the events of all the sessions, which must keep receiving them
+    /// for the sessions to keep reading from their remote node.
+    pub fn new(
+        signer: S,
+        init: Init,
+        chain_hash: [u8; 32],
+        backoff: Backoff,
+        proxy: Option<Socks5Proxy>,
+    ) -> (Self, mpsc::Receiver<PeerEvent>) {
+        let (events, receiver) = mpsc::channel(EVENTS_CAPACITY);
+
+        let inner = Inner {
+            signer,
+            init,
+            chain_hash,
+            backoff,
+            proxy,
+            peers: Mutex::new(HashMap::new()),
+            events,
+            next_session_id: AtomicU64::new(0),
+        };
+
+        (
+            Self {
+                inner: Arc::new(inner),
+            },
+            receiver,
+        )
+    }
+
+    /// Connects to the remote node, unless it is already connected or connected to,
+    /// and reconnects to it whenever its session drops from now on.
+    ///
+    /// Fails when the first attempt fails, in which case the node is not reconnected to,
+    /// or when `disconnect` is called meanwhile.
+    pub async fn connect(&self, node_address: &NodeAddress) -> Result<(), PeerError> {
+        let inner = &self.inner;
+        let node_id = node_address.public_key;
+        let address = &node_address.address;
+
+        let id = inner.next_session_id.fetch_add(1, Ordering::Relaxed);
+
+        // The node is known while it is connected to, so that the other calls for it
+        // do not connect to it again.
+        let task = {
+            let mut peers = inner.peers();
+
+            if let Some(peer) = peers.get_mut(&node_id) {
+                peer.address = Some(address.clone());
+                return Ok(());
+            }
+
+            let task = tokio::spawn({
+                let inner = inner.clone();
+                let address = address.clone();
+                async move { inner.open(node_id, &address).await }
+            });
+
+            let peer = Peer {
+                address: Some(address.clone()),
+                session: None,
+                reconnect: None,
+                connecting: Some(Connecting {
+                    id,
+                    task: task.abort_handle(),
+                }),
+            };
+            peers.insert(node_id, peer);
+
+            task
+        };
+
+        let Ok(opened) = task.await else {
+            return Err(PeerError::Cancelled(hex::encode(node_id.serialize())));
+        };
+
+        match opened {
+            Ok((stream, (reader, writer), init)) => {
+                let direction = Direction::Outbound;
+                inner
+                    .register(node_id, Some(id), direction, stream, reader, writer, init)
+                    .await
+            }
+            Err(e) => {
+                inner.failed(node_id, id);
+                Err(e)
+            }
+        }
+    }
+
+    /// Accepts the connection of a remote node: performs the handshake as the responder
+    /// and exchanges the init messages.
+    ///
+    /// Returns the node id of the remote node.
+    pub async fn accept(&self, mut stream: TcpStream) -> Result<PublicKey, PeerError> {
+        let inner = &self.inner;
+
+        let (mut server_proto, node_id) = ServerProtocol::with_async_signer(&inner.signer)
+            .accept_handshake(&mut stream, &HandshakeTimeouts::default())
+            .await?;
+
+        let init = within(
+            INIT_TIMEOUT,
+            server_proto.exchange_init(&mut stream, &inner.init, &inner.chain_hash),
+        )
+        .await?;
+
+        let (reader, writer) = server_proto.split();
+        inner
+            .register(
+                node_id,
+                None,
+                Direction::Inbound,
+                stream,
+                reader,
+                writer,
+                init,
+            )
+            .await?;
+
+        Ok(node_id)
+    }
+
+    /// Closes the session with the remote node, and stops connecting or reconnecting to it.
+    ///
+    /// Waits for room in the events, which are full while they are not received.
+    pub async fn disconnect(&self, node_id: &PublicKey) -> Result<(), PeerError> {
+        // The room is made before the session is closed, so that its event is not lost.
+        let permit = self.inner.events.reserve().await;
+
+        let peer = self
+            .inner
+            .peers()
+            .remove(node_id)
+            .ok_or_else(|| PeerError::UnknownPeer(hex::encode(node_id.serialize())))?;
+
+        if let Some(reconnect) = peer.reconnect {
+            reconnect.abort();
+        }
+
+        if let Some(connecting) = peer.connecting {
+            connecting.task.abort();
+        }
+
+        if let Some(session) = peer.session {
+            session.task.abort();
+
+            if let Ok(permit) = permit {
+                permit.send(PeerEvent::Disconnected { node_id: *node_id });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the remote nodes, whether they are connected, connected to or reconnected to.
+    pub fn list(&self) -> Vec<PeerInfo> {
+        self.inner
+            .peers()
+            .iter()
+            .map(|(node_id, peer)| PeerInfo {
+                node_id: *node_id,
+                address: peer.address.clone(),
+                direction: peer.session.as_ref().map(|x| x.direction),
+            })
+            .collect()
+    }
+
+    /// Sends the message to the remote node.
+    ///
+    /// Fails when too many messages are already waiting to be sent, e.g. because the remote node
+    /// does not read them.
+    pub fn send_message(&self, node_id: &PublicKey, message: &Message) -> Result<(), PeerError> {
+        let m = message.encode().map_err(ProtocolError::from)?;
+
+        let peers = self.inner.peers();
+        let session = peers.get(node_id).and_then(|x| x.session.as_ref());
+
+        match session.map(|x| x.outbox.try_send(m)) {
+            Some(Ok(())) => Ok(()),
+            Some(Err(TrySendError::Full(_))) => {
+                Err(PeerError::OutboxFull(hex::encode(node_id.serialize())))
+            }
+            Some(Err(TrySendError::Closed(_))) | None => {
+                Err(PeerError::NotConnected(hex::encode(node_id.serialize())))
+            }
+        }
+    }
+}
+
+impl<S> Drop for PeerManager<S> {
+    fn drop(&mut self) {
+        for (_, peer) in self.inner.peers().drain() {
+            if let Some(reconnect) = peer.reconnect {
+                reconnect.abort();
+            }
+
+            if let Some(connecting) = peer.connecting {
+                connecting.task.abort();
+            }
+
+            if let Some(session) = peer.session {
+                session.task.abort();
+            }
+        }
+    }
+}
+
+impl<S> Inner<S> {
+    // Returns the remote nodes, which are only locked while no task is awaited.
+    fn peers(&self) -> MutexGuard<'_, HashMap<PublicKey, Peer>> {
+        self.peers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S: AsyncNodeSigner + 'static> Inner<S> {
+    // Opens a session with the remote node: connects to it, performs the handshake
+    // as the initiator and exchanges the init messages.
+    async fn open(
+        &self,
+        node_id: PublicKey,
+        address: &Address,
+    ) -> Result<(TcpStream, (EncryptedReader, EncryptedWriter), Init), PeerError> {
+        let proxy = self.proxy.as_ref();
+        let mut stream = within(CONNECT_TIMEOUT, address.connect(proxy))
+            .await
+            .map_err(|e| match e {
+                // Without a proxy, the connection failed on its own.
+                PeerError::ProxyFailure { source } if proxy.is_none() => {
+                    PeerError::IoError { source }
+                }
+                e => e,
+            })?;
+
+        let mut client_proto = ClientProtocol::new(node_id)
+            .perform_handshake(&mut stream, &self.signer, &HandshakeTimeouts::default())
+            .await?;
+
+        let init = within(
+            INIT_TIMEOUT,
+            client_proto.exchange_init(&mut stream, &self.init, &self.chain_hash),
+        )
+        .await?;
+
+        Ok((stream, client_proto.split(), init))
+    }
+
+    // Keeps the session with the remote node, unless a session in the other direction
+    // is kept over it, and runs it on its own task.
+    //
+    // The session of a connection opened by `connect` is only kept while the connection
+    // has not been cancelled by `disconnect`.
+    #[allow(clippy::too_many_arguments)]
+    async fn register(
+        self: &Arc<Self>,
+        node_id: PublicKey,
+        connection_id: Option<u64>,
+        direction: Direction,
+        stream: TcpStream,
+        reader: EncryptedReader,
+        writer: EncryptedWriter,
+        init: Init,
+    ) -> Result<(), PeerError> {
+        // The room is made before the lock is taken, so that the event is not lost.
+        let permit = self.events.reserve().await;
+
+        let mut peers = self.peers();
+
+        let peer = match connection_id {
+            Some(id) => match peers.get_mut(&node_id) {
+                Some(peer) if peer.connecting.as_ref().map(|x| x.id) == Some(id) => {
+                    peer.connecting = None;
+                    peer
+                }
+                // The stream is closed once dropped.
+                _ => return Err(PeerError::Cancelled(hex::encode(node_id.serialize()))),
+            },
+            None => peers.entry(node_id).or_insert(Peer {
+                address: None,
+                session: None,
+                reconnect: None,
+                connecting: None,
+            }),
+        };
+
+        // The reconnecting task may be the caller, which returns right after.
+        if let Some(reconnect) = peer.reconnect.take() {
+            reconnect.abort();
+        }
+
+        match &peer.session {
+            Some(session) if session.direction != direction && !self.keeps(direction, node_id) => {
+                // The stream is closed once dropped.
+                return Ok(());
+            }
+            // The previous session is replaced: either it is in the same direction, since the
+            // remote node connected again before the session was found to be dropped,
+            // or it is in the other direction and the new session is kept over it.
+            Some(session) => session.task.abort(),
+            None => {
+                if let Ok(permit) = permit {
+                    permit.send(PeerEvent::Connected { node_id, init });
+                }
+            }
+        }
+
+        let id = self.next_session_id.fetch_add(1, Ordering::Relaxed);
+        let (outbox, receiver) = mpsc::channel(OUTBOX_CAPACITY);
+
+        let inner = self.clone();
+        let task = tokio::spawn(async move {
+            // The session ends when the remote node fails or disconnects,
+            // which is told to the caller by the event.
+            let _ = inner.run(node_id, stream, reader, writer, receiver).await;
+
+            let permit = inner.events.reserve().await;
+            inner.closed(node_id, id, permit.ok());
+        });
+
+        peer.session = Some(Session {
+            id,
+            direction,
+            outbox,
+            task,
+        });
+
+        Ok(())
+    }
+
+    // Forgets the connection opened by `connect` once it has failed, unless it was cancelled,
+    // along with the remote node unless it connected meanwhile.
+    fn failed(&self, node_id: PublicKey, id: u64) {
+        let mut peers = self.peers();
+
+        let Some(peer) = peers.get_mut(&node_id) else {
+            return;
+        };
+
+        if peer.connecting.as_ref().map(|x| x.id) != Some(id) {
+            return;
+        }
+
+        peer.connecting = None;
+
+        // The node is not reconnected to, since the first attempt failed.
+        match peer.session {
+            Some(_) => peer.address = None,
+            None => {
+                peers.remove(&node_id);
+            }
+        }
+    }
+
+    // Returns whether a session in the direction is kept over a session in the other direction,
+    // which is the case when it is opened by the node with the lowest node id.
+    fn keeps(&self, direction: Direction, node_id: PublicKey) -> bool {
+        let ls_pk = self.signer.node_id();
+        let local_is_lowest = ls_pk.serialize() < node_id.serialize();
+
+        (direction == Direction::Outbound) == local_is_lowest
+    }
+
+    // Reads the messages of the remote node while keeping the session alive,
+    // and sends the messages of the outbox on a separate task.
+    //
+    // A remote node that stops reading only stalls the writing task, so its pings
+    // still go unanswered and the keepalive ends the session.
+    async fn run(
+        &self,
+        node_id: PublicKey,
+        stream: TcpStream,
+        mut reader: EncryptedReader,
+        writer: EncryptedWriter,
+        outbox: mpsc::Receiver<Vec<u8>>,
+    ) -> Result<(), ProtocolError> {
+        let (mut read_half, write_half) = stream.into_split();
+        let mut keepalive = Keepalive::default();
+
+        let (replies, replies_receiver) = mpsc::channel(2);
+        let mut writing = Writing(tokio::spawn(write(
+            writer,
+            write_half,
+            replies_receiver,
+            outbox,
+        )));
+
+        loop {
+            let reply = tokio::select! {
+                m = reader.read_message(&mut read_half) => {
+                    match keepalive.on_message(Message::decode(&m?)?)? {
+                        KeepaliveOutcome::Reply(pong) => pong.encode()?,
+                        KeepaliveOutcome::Handled => continue,
+                        KeepaliveOutcome::Message(message) => {
+                            // The remote node is not read from while the events are full.
+                            let _ = self.events.send(PeerEvent::Message { node_id, message }).await;
+                            continue;
+                        }
+                    }
+                }
+                _ = time::sleep_until(keepalive.deadline()) => keepalive.on_deadline()?.encode()?,
+                r = &mut writing.0 => return r.map_err(std::io::Error::from)?,
+            };
+
+            // The replies only queue up while the writing task is stalled,
+            // in which case they would not be sent in time anyway.
+            let _ = replies.try_send(reply);
+        }
+    }
+
+    // Forgets the session once it has ended, unless it was replaced,
+    // and reconnects to the remote node if it was connected to with `connect`.
+    //
+    // The room for its event is made by the caller, before the lock is taken.
+    fn closed(
+        self: &Arc<Self>,
+        node_id: PublicKey,
+        id: u64,
+        permit: Option<Permit<'_, PeerEvent>>,
+    ) {
+        let mut peers = self.peers();
+
+        let Some(peer) = peers.get_mut(&node_id) else {
+            return;
+        };
+
+        if peer.session.as_ref().map(|x| x.id) != Some(id) {
+            return;
+        }
+
+        peer.session = None;
+
+        if let Some(permit) = permit {
+            permit.send(PeerEvent::Disconnected { node_id });
+        }
+
+        match peer.address.clone() {
+            // The connection opened by `connect` takes over.
+            Some(_) if peer.connecting.is_some() => {}
+            Some(address) => {
+                peer.reconnect = Some(tokio::spawn(self.clone().reconnect(node_id, address)));
+            }
+            None => {
+                peers.remove(&node_id);
+            }
+        }
+    }
+
+    // Attempts to open a session with the remote node until it succeeds,
+    // waiting for the backoff before every attempt.
+    async fn reconnect(self: Arc<Self>, node_id: PublicKey, address: Address) {
+        let mut attempt = 0;
+
+        loop {
+            time::sleep(self.backoff.delay(attempt)).await;
+
+            if let Ok((stream, (reader, writer), init)) = self.open(node_id, &address).await {
+                let _ = self
+                    .register(
+                        node_id,
+                        None,
+                        Direction::Outbound,
+                        stream,
+                        reader,
+                        writer,
+                        init,
+                    )
+                    .await;
+
+                return;
+            }
+
+            attempt = attempt.saturating_add(1);
+        }
+    }
+}
+
+// Sends the replies of the keepalive, and the messages of the outbox until it is closed.
+async fn write(
+    mut writer: EncryptedWriter,
+    mut write_half: OwnedWriteHalf,
+    mut replies: mpsc::Receiver<Vec<u8>>,
+    mut outbox: mpsc::Receiver<Vec<u8>>,
+) -> Result<(), ProtocolError> {
+    loop {
+        let m = tokio::select! {
+            biased;
+            Some(m) = replies.recv() => m,
+            m = outbox.recv() => match m {
+                Some(m) => m,
+                None => return Ok(()),
+            },
+        };
+
+        writer.send_message(&mut write_half, &m).await?;
+    }
+}
+
+/// The task writing to a remote node, which is aborted once the session ends,
+/// including when the task running the session is aborted.
+struct Writing(JoinHandle<Result<(), ProtocolError>>);
+
+impl Drop for Writing {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// Runs the future, which fails with a timeout when it does not complete in time.
+async fn within<T, E>(
+    duration: Duration,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, PeerError>
+where
+    PeerError: From<E>,
+{
+    match time::timeout(duration, future).await {
+        Ok(x) => Ok(x?),
+        Err(_) => Err(PeerError::Timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bolt_1::message::Warning,
+        bolt_7::address::Host,
+        bolt_8::protocol::mock_peer::{MockPeer, Step, HANDSHAKE},
+        signer::InMemorySigner,
+    };
+    use secp256k1::{SecretKey, SECP256K1};
+    use std::{net::Ipv4Addr, sync::Weak};
+    use tokio::net::{TcpListener, TcpSocket};
+
+    // Returns a peer manager accepting the connections on a local TCP port,
+    // along with its events and its address.
+    async fn manager(
+        ls_sk: SecretKey,
+    ) -> (
+        Arc<PeerManager<InMemorySigner>>,
+        mpsc::Receiver<PeerEvent>,
+        NodeAddress,
+    ) {
+        let backoff = Backoff::new(Duration::from_millis(10), Duration::from_millis(100));
+        let signer = InMemorySigner::new(ls_sk);
+        let (manager, events) = PeerManager::new(signer, Init::default(), [0; 32], backoff, None);
+        let manager = Arc::new(manager);
+
+        // The receive buffers of the connections are kept small, so they are quick to fill.
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_recv_buffer_size(64 * 1024).unwrap();
+        socket.bind((Ipv4Addr::LOCALHOST, 0).into()).unwrap();
+        let listener = socket.listen(1024).unwrap();
+
+        let node_address = NodeAddress {
+            public_key: PublicKey::from_secret_key(SECP256K1, &ls_sk),
+            address: Address {
+                host: Host::Ipv4(Ipv4Addr::LOCALHOST),
+                port: listener.local_addr().unwrap().port(),
+            },
+        };
+
+        let weak = Arc::downgrade(&manager);
+        tokio::spawn(accept(listener, weak));
+
+        (manager, events, node_address)
+    }
+
+    // Accepts the connections until the peer manager is dropped.
+    async fn accept(listener: TcpListener, manager: Weak<PeerManager<InMemorySigner>>) {
+        while let Ok((stream, _)) = listener.accept().await {
+            let Some(manager) = manager.upgrade() else {
+                return;
+            };
+
+            tokio::spawn(async move {
+                let _ = manager.accept(stream).await;
+            });
+        }
+    }
+
+    // Returns the next event, which must come in time.
+    async fn event(events: &mut mpsc::Receiver<PeerEvent>) -> PeerEvent {
+        time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    // Returns the direction of the session with the remote node, once it is connected.
+    async fn direction(manager: &PeerManager<InMemorySigner>, node_id: PublicKey) -> Direction {
+        for _ in 0..500 {
+            let direction = manager
+                .list()
+                .into_iter()
+                .find(|x| x.node_id == node_id)
+                .and_then(|x| x.direction);
+
+            if let Some(direction) = direction {
+                return direction;
+            }
+
+            time::sleep(Duration::from_millis(10)).await;
+        }
+
+        panic!("The node is not connected");
+    }
+
+    fn warning(data: &[u8]) -> Message {
+        Message::Warning(Warning {
+            channel_id: [0; 32],
+            data: data.to_vec(),
+        })
+    }
+
+    fn random_key() -> SecretKey {
+        SecretKey::new(&mut secp256k1::rand::thread_rng())
+    }
+
+    #[tokio::test]
+    async fn it_exchanges_messages_with_the_connected_nodes() {
+        let (alice, mut alice_events, alice_address) = manager(random_key()).await;
+        let (bob, mut bob_events, bob_address) = manager(random_key()).await;
+        let alice_id = alice_address.public_key;
+        let bob_id = bob_address.public_key;
+
+        alice.connect(&bob_address).await.unwrap();
+
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Connected { node_id: bob_id, init: Init::default() } };
+        assert_eq! { event(&mut bob_events).await, PeerEvent::Connected { node_id: alice_id, init: Init::default() } };
+
+        assert_eq! {
+            alice.list(),
+            vec![PeerInfo { node_id: bob_id, address: Some(bob_address.address.clone()), direction: Some(Direction::Outbound) }]
+        };
+        assert_eq! {
+            bob.list(),
+            vec![PeerInfo { node_id: alice_id, address: None, direction: Some(Direction::Inbound) }]
+        };
+
+        alice.send_message(&bob_id, &warning(b"hello")).unwrap();
+        bob.send_message(&alice_id, &warning(b"world")).unwrap();
+
+        assert_eq! { event(&mut bob_events).await, PeerEvent::Message { node_id: alice_id, message: warning(b"hello") } };
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Message { node_id: bob_id, message: warning(b"world") } };
+
+        // Connecting again keeps the session.
+        alice.connect(&bob_address).await.unwrap();
+
+        assert_eq! { alice.list().len(), 1 };
+    }
+
+    #[tokio::test]
+    async fn it_reconnects_when_the_remote_node_disconnects() {
+        let (alice, mut alice_events, _) = manager(random_key()).await;
+        let (bob, mut bob_events, bob_address) = manager(random_key()).await;
+        let bob_id = bob_address.public_key;
+
+        alice.connect(&bob_address).await.unwrap();
+
+        let alice_id = match event(&mut bob_events).await {
+            PeerEvent::Connected { node_id, .. } => node_id,
+            x => panic!("Unexpected event {x:?}"),
+        };
+        event(&mut alice_events).await;
+
+        bob.disconnect(&alice_id).await.unwrap();
+
+        assert_eq! { event(&mut bob_events).await, PeerEvent::Disconnected { node_id: alice_id } };
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Disconnected { node_id: bob_id } };
+
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Connected { node_id: bob_id, init: Init::default() } };
+        assert_eq! { event(&mut bob_events).await, PeerEvent::Connected { node_id: alice_id, init: Init::default() } };
+
+        alice.send_message(&bob_id, &warning(b"hello")).unwrap();
+
+        assert_eq! { event(&mut bob_events).await, PeerEvent::Message { node_id: alice_id, message: warning(b"hello") } };
+    }
+
+    #[tokio::test]
+    async fn it_keeps_a_single_session_when_both_nodes_connect() {
+        let (alice, mut alice_events, alice_address) = manager(random_key()).await;
+        let (bob, mut bob_events, bob_address) = manager(random_key()).await;
+        let alice_id = alice_address.public_key;
+        let bob_id = bob_address.public_key;
+
+        let (a, b) = tokio::join!(alice.connect(&bob_address), bob.connect(&alice_address));
+        a.unwrap();
+        b.unwrap();
+
+        let alice_direction = direction(&alice, bob_id).await;
+        let bob_direction = direction(&bob, alice_id).await;
+
+        assert_ne! { alice_direction, bob_direction };
+        assert_eq! {
+            alice_direction == Direction::Outbound,
+            alice_id.serialize() < bob_id.serialize()
+        };
+
+        alice.send_message(&bob_id, &warning(b"hello")).unwrap();
+
+        loop {
+            match event(&mut bob_events).await {
+                PeerEvent::Message { node_id, message } => {
+                    assert_eq! { node_id, alice_id };
+                    assert_eq! { message, warning(b"hello") };
+                    break;
+                }
+                _ => continue,
+            }
+        }
+
+        assert_eq! { alice.list().len(), 1 };
+        assert_eq! { bob.list().len(), 1 };
+
+        while let Ok(x) = alice_events.try_recv() {
+            assert!(
+                !matches!(x, PeerEvent::Message { .. }),
+                "Unexpected event {x:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn it_stops_reconnecting_once_disconnected() {
+        let (alice, mut alice_events, _) = manager(random_key()).await;
+        let (bob, mut bob_events, bob_address) = manager(random_key()).await;
+        let bob_id = bob_address.public_key;
+
+        alice.connect(&bob_address).await.unwrap();
+
+        event(&mut alice_events).await;
+        let alice_id = match event(&mut bob_events).await {
+            PeerEvent::Connected { node_id, .. } => node_id,
+            x => panic!("Unexpected event {x:?}"),
+        };
+
+        alice.disconnect(&bob_id).await.unwrap();
+
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Disconnected { node_id: bob_id } };
+        assert_eq! { event(&mut bob_events).await, PeerEvent::Disconnected { node_id: alice_id } };
+
+        time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq! { alice.list(), vec![] };
+        assert_eq! { bob.list(), vec![] };
+        assert!(alice_events.try_recv().is_err());
+        assert!(bob_events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn it_ends_the_session_when_the_remote_node_stops_reading() {
+        let (alice, mut alice_events, alice_address) = manager(random_key()).await;
+        let bob_sk = random_key();
+        let bob_id = PublicKey::from_secret_key(SECP256K1, &bob_sk);
+
+        // Bob connects to Alice, then never reads from the stream.
+        let port = alice_address.address.port;
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+            .await
+            .unwrap();
+        let mut client_proto = ClientProtocol::new(alice_address.public_key)
+            .perform_handshake(
+                &mut stream,
+                InMemorySigner::new(bob_sk),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        client_proto
+            .exchange_init(&mut stream, &Init::default(), &[0; 32])
+            .await
+            .unwrap();
+
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Connected { node_id: bob_id, init: Init::default() } };
+
+        // The messages fill the buffers of the connection, then the outbox.
+        let full = loop {
+            match alice.send_message(&bob_id, &warning(&[0; 60000])) {
+                Ok(()) => time::sleep(Duration::from_millis(1)).await,
+                Err(e) => break e,
+            }
+        };
+        assert!(matches!(full, PeerError::OutboxFull(_)));
+
+        // Bob answers no ping, which the keepalive notices while the writing task is stalled.
+        time::pause();
+
+        let disconnected = time::timeout(Duration::from_secs(300), alice_events.recv()).await;
+        assert_eq! { disconnected.unwrap(), Some(PeerEvent::Disconnected { node_id: bob_id }) };
+        assert_eq! { alice.list(), vec![] };
+    }
+
+    #[tokio::test]
+    async fn it_connects_once_when_connecting_twice_at_the_same_time() {
+        let (alice, mut alice_events, _) = manager(random_key()).await;
+        let bob_sk = random_key();
+        let bob_id = PublicKey::from_secret_key(SECP256K1, &bob_sk);
+
+        // The mock peer accepts a single connection, so a second one would fail.
+        let script = HANDSHAKE.into_iter().chain([
+            Step::Read,
+            Step::Send(Message::Init(Init::default())),
+            Step::Stall,
+        ]);
+        let (address, _peer) = MockPeer::new(bob_sk, script).listen().await;
+
+        let bob_address = NodeAddress {
+            public_key: bob_id,
+            address: Address {
+                host: Host::Ipv4(Ipv4Addr::LOCALHOST),
+                port: address.port(),
+            },
+        };
+
+        let (a, b) = tokio::join!(alice.connect(&bob_address), alice.connect(&bob_address));
+        a.unwrap();
+        b.unwrap();
+
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Connected { node_id: bob_id, init: Init::default() } };
+        assert_eq! { direction(&alice, bob_id).await, Direction::Outbound };
+
+        time::sleep(Duration::from_millis(200)).await;
+
+        assert!(alice_events.try_recv().is_err());
+        assert_eq! { alice.list().len(), 1 };
+    }
+
+    #[tokio::test]
+    async fn it_cancels_the_connection_when_disconnected_meanwhile() {
+        let (alice, mut alice_events, _) = manager(random_key()).await;
+        let bob_sk = random_key();
+        let bob_id = PublicKey::from_secret_key(SECP256K1, &bob_sk);
+
+        // The mock peer never answers the handshake.
+        let (address, _peer) = MockPeer::new(bob_sk, [Step::ReadAct1, Step::Stall])
+            .listen()
+            .await;
+
+        let bob_address = NodeAddress {
+            public_key: bob_id,
+            address: Address {
+                host: Host::Ipv4(Ipv4Addr::LOCALHOST),
+                port: address.port(),
+            },
+        };
+
+        let connect = tokio::spawn({
+            let alice = alice.clone();
+            async move { alice.connect(&bob_address).await }
+        });
+
+        while alice.list().is_empty() {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq! { alice.list()[0].direction, None };
+
+        alice.disconnect(&bob_id).await.unwrap();
+
+        assert!(matches!(
+            connect.await.unwrap(),
+            Err(PeerError::Cancelled(_))
+        ));
+        assert_eq! { alice.list(), vec![] };
+        assert!(alice_events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn it_stops_reading_from_the_remote_node_while_the_events_are_not_received() {
+        let (_alice, mut alice_events, alice_address) = manager(random_key()).await;
+        let bob_sk = random_key();
+        let bob_id = PublicKey::from_secret_key(SECP256K1, &bob_sk);
+
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_send_buffer_size(64 * 1024).unwrap();
+        let port = alice_address.address.port;
+        let mut stream = socket
+            .connect((Ipv4Addr::LOCALHOST, port).into())
+            .await
+            .unwrap();
+        let mut client_proto = ClientProtocol::new(alice_address.public_key)
+            .perform_handshake(
+                &mut stream,
+                InMemorySigner::new(bob_sk),
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+        client_proto
+            .exchange_init(&mut stream, &Init::default(), &[0; 32])
+            .await
+            .unwrap();
+
+        // Bob floods Alice, whose events are not received, until the buffers of the connection
+        // are full, since Alice stops reading once the events are full.
+        let m = warning(&[0; 1000]).encode().unwrap();
+        let mut sent = 0;
+        while let Ok(x) = time::timeout(
+            Duration::from_millis(500),
+            client_proto.send_message(&mut stream, &m),
+        )
+        .await
+        {
+            x.unwrap();
+            sent += 1;
+        }
+        assert!(sent >= EVENTS_CAPACITY);
+
+        // Once the events are received, Alice reads the messages again.
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Connected { node_id: bob_id, init: Init::default() } };
+
+        for _ in 0..sent {
+            assert_eq! { event(&mut alice_events).await, PeerEvent::Message { node_id: bob_id, message: warning(&[0; 1000]) } };
+        }
+    }
+
+    #[tokio::test]
+    async fn it_ends_the_session_on_a_message_of_an_unknown_even_type() {
+        let (alice, mut alice_events, _) = manager(random_key()).await;
+        let bob_sk = random_key();
+        let bob_id = PublicKey::from_secret_key(SECP256K1, &bob_sk);
+
+        let unknown_even = Message::UnknownEven {
+            message_type: 0x8000,
+            payload: vec![],
+        };
+        let script = HANDSHAKE.into_iter().chain([
+            Step::Read,
+            Step::Send(Message::Init(Init::default())),
+            Step::Send(unknown_even),
+            Step::Stall,
+        ]);
+        let (address, _peer) = MockPeer::new(bob_sk, script).listen().await;
+
+        let bob_address = NodeAddress {
+            public_key: bob_id,
+            address: Address {
+                host: Host::Ipv4(Ipv4Addr::LOCALHOST),
+                port: address.port(),
+            },
+        };
+        alice.connect(&bob_address).await.unwrap();
+
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Connected { node_id: bob_id, init: Init::default() } };
+        assert_eq! { event(&mut alice_events).await, PeerEvent::Disconnected { node_id: bob_id } };
+    }
+
+    #[tokio::test]
+    async fn it_fails_to_reach_unknown_or_unreachable_nodes() {
+        let (alice, _alice_events, _) = manager(random_key()).await;
+        let (_bob, _bob_events, mut bob_address) = manager(random_key()).await;
+        let node_id = PublicKey::from_secret_key(SECP256K1, &random_key());
+
+        assert!(matches!(
+            alice.disconnect(&node_id).await,
+            Err(PeerError::UnknownPeer(_))
+        ));
+        assert!(matches!(
+            alice.send_message(&node_id, &warning(b"hello")),
+            Err(PeerError::NotConnected(_))
+        ));
+
+        // The remote node has another node id.
+        let wrong_node = NodeAddress {
+            public_key: node_id,
+            address: bob_address.address.clone(),
+        };
+        assert!(matches!(
+            alice.connect(&wrong_node).await,
+            Err(PeerError::SessionFailure { .. })
+        ));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        bob_address.address.port = listener.local_addr().unwrap().port();
+        drop(listener);
+        assert!(matches!(
+            alice.connect(&bob_address).await,
+            Err(PeerError::IoError { .. })
+        ));
+
+        bob_address.address.host = Host::TorV3([0; 35]);
+        assert!(matches!(
+            alice.connect(&bob_address).await,
+            Err(PeerError::ProxyRequired)
+        ));
+
+        assert_eq! { alice.list(), vec![] };
+    }
+}
@@ -0,0 +1,28 @@
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("IO error")]
+    IoError { source: eyre::Report },
+
+    #[error("The signer returned the invalid node id '{hex}'")]
+    InvalidNodeId { hex: String, source: eyre::Report },
+
+    #[error("The signer returned the node id '{hex}' after reconnecting, instead of its own")]
+    UnexpectedNodeId { hex: String },
+
+    #[error("The signer rejected the request with the status '{0}'")]
+    Rejected(u8),
+
+    #[error("The signer address '{0}' is not a loopback address")]
+    NonLoopbackAddress(std::net::SocketAddr),
+
+    #[error("The signer did not answer in time")]
+    Timeout,
+}
+
+impl From<std::io::Error> for SignerError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError {
+            source: eyre::Report::new(e),
+        }
+    }
+}
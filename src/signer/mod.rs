@@ -0,0 +1,201 @@
+//! This module defines the signers, which hold the static secret key of the local node
+//! and perform the operations that require it, so that the key may live outside of the process.
+
+mod error;
+#[cfg(feature = "async")]
+mod remote;
+
+pub use self::error::SignerError;
+#[cfg(feature = "async")]
+pub use self::remote::RemoteSigner;
+
+use crate::bolt_8::crypto::{ecdh, Secret};
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::sync::Arc;
+
+/// Performs the operations that require the static secret key of the local node.
+///
+/// The client keeps the signer until the end of the handshake, so the signers that hold
+/// the secret key should wipe it when dropped, like the `InMemorySigner` does.
+pub trait NodeSigner: Send + Sync {
+    /// Returns the node id, which is the static public key of the local node.
+    fn node_id(&self) -> PublicKey;
+
+    /// Performs an Elliptic-Curve Diffie-Hellman operation between the public key
+    /// and the static secret key of the local node.
+    ///
+    /// Returns the SHA256 digest of the generated point.
+    fn ecdh(&self, pk: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError>;
+}
+
+impl<S: NodeSigner> NodeSigner for &S {
+    fn node_id(&self) -> PublicKey {
+        (**self).node_id()
+    }
+
+    fn ecdh(&self, pk: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+        (**self).ecdh(pk)
+    }
+}
+
+impl<S: NodeSigner> NodeSigner for Arc<S> {
+    fn node_id(&self) -> PublicKey {
+        (**self).node_id()
+    }
+
+    fn ecdh(&self, pk: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+        (**self).ecdh(pk)
+    }
+}
+
+/// Performs the operations that require the static secret key of the local node,
+/// when the signer has to wait for them, e.g. because the key lives in another process.
+///
+/// Only the asynchronous client accepts these signers, as the core of the handshake
+/// and the blocking client never wait on a signer.
+#[cfg(feature = "async")]
+pub trait AsyncNodeSigner: Send + Sync {
+    /// Returns the node id, which is the static public key of the local node.
+    fn node_id(&self) -> PublicKey;
+
+    /// Performs an Elliptic-Curve Diffie-Hellman operation between the public key
+    /// and the static secret key of the local node.
+    ///
+    /// Returns the SHA256 digest of the generated point.
+    fn ecdh(
+        &self,
+        pk: &PublicKey,
+    ) -> impl Future<Output = Result<Secret<[u8; 32]>, SignerError>> + Send;
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncNodeSigner> AsyncNodeSigner for &S {
+    fn node_id(&self) -> PublicKey {
+        (**self).node_id()
+    }
+
+    fn ecdh(
+        &self,
+        pk: &PublicKey,
+    ) -> impl Future<Output = Result<Secret<[u8; 32]>, SignerError>> + Send {
+        (**self).ecdh(pk)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: AsyncNodeSigner> AsyncNodeSigner for Arc<S> {
+    fn node_id(&self) -> PublicKey {
+        (**self).node_id()
+    }
+
+    fn ecdh(
+        &self,
+        pk: &PublicKey,
+    ) -> impl Future<Output = Result<Secret<[u8; 32]>, SignerError>> + Send {
+        (**self).ecdh(pk)
+    }
+}
+
+/// A signer that keeps the static secret key of the local node in memory.
+#[derive(Debug)]
+pub struct InMemorySigner {
+    /// The static secret key of the local node.
+    ls_sk: Secret<SecretKey>,
+}
+
+impl InMemorySigner {
+    /// Creates a signer with the static secret key of the local node.
+    pub fn new(ls_sk: SecretKey) -> Self {
+        Self {
+            ls_sk: Secret::new(ls_sk),
+        }
+    }
+}
+
+impl NodeSigner for InMemorySigner {
+    fn node_id(&self) -> PublicKey {
+        PublicKey::from_secret_key(SECP256K1, &self.ls_sk)
+    }
+
+    fn ecdh(&self, pk: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+        Ok(ecdh(pk, &self.ls_sk))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncNodeSigner for InMemorySigner {
+    fn node_id(&self) -> PublicKey {
+        NodeSigner::node_id(self)
+    }
+
+    async fn ecdh(&self, pk: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+        NodeSigner::ecdh(self, pk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bolt_8::crypto::read_after_drop;
+    use hex_literal::hex;
+
+    fn shared_secret(signer: impl NodeSigner, pk: &PublicKey) -> [u8; 32] {
+        *signer.ecdh(pk).unwrap()
+    }
+
+    #[test]
+    fn it_performs_the_ecdh_with_the_static_key() {
+        // Values used for testing are taken from BOLT-8 test vectors.
+        // See: https://github.com/lightning/bolts/blob/master/08-transport.md#appendix-a-transport-test-vectors
+
+        let ls_sk = hex!("2121212121212121212121212121212121212121212121212121212121212121");
+        let signer = InMemorySigner::new(SecretKey::from_slice(&ls_sk).unwrap());
+
+        let re_pk = hex!("036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f7");
+        let re_pk = PublicKey::from_slice(&re_pk).unwrap();
+
+        assert_eq! {
+            NodeSigner::node_id(&signer).serialize(),
+            hex!("028d7500dd4c12685d1f568b4c2b5048e8534b873319f3a8daa612b469132ec7f7")
+        };
+        assert_eq! {
+            *NodeSigner::ecdh(&signer, &re_pk).unwrap(),
+            hex!("1e2fb3c8fe8fb9f262f649f64d26ecf0f2c0a805a767cf02dc2d77a6ef1fdcc3")
+        };
+
+        // The references and the shared signers delegate to the signer.
+        assert_eq! {
+            shared_secret(&signer, &re_pk),
+            shared_secret(Arc::new(signer), &re_pk)
+        };
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    async fn it_performs_the_same_ecdh_asynchronously() {
+        let signer = InMemorySigner::new(SecretKey::new(&mut secp256k1::rand::thread_rng()));
+        let pk = PublicKey::from_secret_key(
+            SECP256K1,
+            &SecretKey::new(&mut secp256k1::rand::thread_rng()),
+        );
+
+        assert_eq! {
+            *AsyncNodeSigner::ecdh(&signer, &pk).await.unwrap(),
+            *NodeSigner::ecdh(&signer, &pk).unwrap()
+        };
+        assert_eq! { AsyncNodeSigner::node_id(&signer), NodeSigner::node_id(&signer) };
+    }
+
+    #[test]
+    fn it_zeroizes_and_redacts_the_secret_key() {
+        let ls_sk = SecretKey::from_slice(&[0x11; 32]).unwrap();
+
+        assert_eq! { read_after_drop(InMemorySigner::new(ls_sk), |x| &*x.ls_sk).secret_bytes(), [1; 32] };
+
+        let debug = format!("{:?}", InMemorySigner::new(ls_sk));
+
+        assert_eq! { debug, "InMemorySigner { ls_sk: Secret([REDACTED]) }" };
+    }
+}
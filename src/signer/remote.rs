@@ -0,0 +1,377 @@
+use crate::{
+    bolt_8::crypto::Secret,
+    signer::{AsyncNodeSigner, SignerError},
+};
+use secp256k1::PublicKey;
+use std::{future::Future, net::SocketAddr, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::Mutex,
+    time,
+};
+
+/// The request for the node id, which has no payload.
+const NODE_ID: u8 = 1;
+
+/// The request for an ECDH, followed by the 33-byte compressed public key.
+const ECDH: u8 = 2;
+
+/// The status of a successful response, which is followed by its payload.
+const OK: u8 = 0;
+
+/// A signer that keeps the static secret key of the local node in a separate process,
+/// reached over a TCP connection on the same host.
+///
+/// The connection is neither encrypted nor authenticated, so only loopback addresses
+/// are accepted, which other hosts can neither reach nor listen on.
+///
+/// Every request starts with its 1-byte type followed by its payload,
+/// and every response starts with a 1-byte status:
+///     - the node id request is answered with the 33-byte compressed node id;
+///     - the ECDH request is answered with the 32-byte digest of the generated point;
+///     - a non-zero status rejects the request, and is not followed by any payload;
+///
+/// The requests are sent one at a time over a single connection,
+/// which is opened again when a request fails, times out or is cancelled.
+#[derive(Debug)]
+pub struct RemoteSigner {
+    /// The address of the signer.
+    address: SocketAddr,
+
+    /// The time the signer has to connect or to answer a request.
+    timeout: Duration,
+
+    /// The node id, which is requested on every connection.
+    node_id: PublicKey,
+
+    /// The connection to the signer, which is missing after a failed or cancelled request.
+    stream: Mutex<Option<TcpStream>>,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer on a loopback address and requests the node id.
+    ///
+    /// Fails when the signer does not connect or answer a request within the timeout.
+    pub async fn connect(address: SocketAddr, timeout: Duration) -> Result<Self, SignerError> {
+        if !address.ip().is_loopback() {
+            return Err(SignerError::NonLoopbackAddress(address));
+        }
+
+        let (stream, node_id) = open(address, timeout).await?;
+
+        Ok(Self {
+            address,
+            timeout,
+            node_id,
+            stream: Mutex::new(Some(stream)),
+        })
+    }
+}
+
+impl AsyncNodeSigner for RemoteSigner {
+    fn node_id(&self) -> PublicKey {
+        self.node_id
+    }
+
+    async fn ecdh(&self, pk: &PublicKey) -> Result<Secret<[u8; 32]>, SignerError> {
+        let mut message = [0u8; 34];
+        message[0] = ECDH;
+        message[1..].copy_from_slice(&pk.serialize());
+
+        // The connection is taken out while the request is in flight, so that a cancelled request
+        // does not leave its response to be read by the next one.
+        let mut guard = self.stream.lock().await;
+
+        // Another process may have taken the address over since the last connection,
+        // so the node id is checked again before sending any request to it.
+        let mut stream = match guard.take() {
+            Some(stream) => stream,
+            None => {
+                let (stream, node_id) = open(self.address, self.timeout).await?;
+
+                if node_id != self.node_id {
+                    return Err(SignerError::UnexpectedNodeId {
+                        hex: hex::encode(node_id.serialize()),
+                    });
+                }
+
+                stream
+            }
+        };
+
+        let mut ss = Secret::new([0u8; 32]);
+        bounded(self.timeout, request(&mut stream, &message, &mut *ss)).await?;
+
+        *guard = Some(stream);
+
+        Ok(ss)
+    }
+}
+
+// Connects to the signer and requests the node id.
+async fn open(
+    address: SocketAddr,
+    timeout: Duration,
+) -> Result<(TcpStream, PublicKey), SignerError> {
+    let mut stream = bounded(timeout, async { Ok(TcpStream::connect(address).await?) }).await?;
+
+    let mut node_id = [0u8; 33];
+    bounded(timeout, request(&mut stream, &[NODE_ID], &mut node_id)).await?;
+
+    let node_id = PublicKey::from_slice(&node_id).map_err(|e| SignerError::InvalidNodeId {
+        hex: hex::encode(node_id),
+        source: eyre::Report::new(e),
+    })?;
+
+    Ok((stream, node_id))
+}
+
+// Fails when the signer does not complete the operation within the timeout.
+async fn bounded<T>(
+    timeout: Duration,
+    operation: impl Future<Output = Result<T, SignerError>>,
+) -> Result<T, SignerError> {
+    time::timeout(timeout, operation)
+        .await
+        .map_err(|_| SignerError::Timeout)?
+}
+
+// Sends the request and reads the payload of a successful response.
+async fn request(
+    stream: &mut TcpStream,
+    message: &[u8],
+    payload: &mut [u8],
+) -> Result<(), SignerError> {
+    stream.write_all(message).await?;
+
+    match stream.read_u8().await? {
+        OK => {
+            stream.read_exact(payload).await?;
+            Ok(())
+        }
+        status => Err(SignerError::Rejected(status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bolt_8::protocol::{ClientProtocol, HandshakeTimeouts, ServerProtocol},
+        signer::{InMemorySigner, NodeSigner},
+    };
+    use secp256k1::{SecretKey, SECP256K1};
+    use tokio::net::TcpListener;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // Runs a stand-in signer with the secret key, which answers the requests of every connection
+    // until it has answered the given number of requests, then rejects the following ones.
+    async fn stand_in(ls_sk: SecretKey, answered: usize) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut answered = answered;
+
+            while let Ok((mut stream, _)) = listener.accept().await {
+                while let Ok(request) = stream.read_u8().await {
+                    let mut response = match request {
+                        NODE_ID => {
+                            let node_id = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+                            [&[OK][..], &node_id.serialize()].concat()
+                        }
+                        ECDH => {
+                            let mut pk = [0u8; 33];
+                            if stream.read_exact(&mut pk).await.is_err() {
+                                break;
+                            }
+
+                            let pk = PublicKey::from_slice(&pk).unwrap();
+                            let ss = crate::bolt_8::crypto::ecdh(&pk, &ls_sk);
+                            [&[OK][..], &*ss].concat()
+                        }
+                        _ => vec![0xff],
+                    };
+
+                    if answered == 0 {
+                        response = vec![0x01];
+                    } else {
+                        answered -= 1;
+                    }
+
+                    // The client may have dropped the connection of a cancelled request.
+                    let _ = stream.write_all(&response).await;
+                }
+            }
+        });
+
+        address
+    }
+
+    // Runs a signer with the secret key, which answers the node id requests
+    // but never answers the ECDH requests.
+    async fn stalling(ls_sk: SecretKey) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    while let Ok(NODE_ID) = stream.read_u8().await {
+                        let node_id = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+                        let response = [&[OK][..], &node_id.serialize()].concat();
+                        stream.write_all(&response).await.unwrap();
+                    }
+
+                    std::future::pending::<()>().await;
+                });
+            }
+        });
+
+        address
+    }
+
+    #[tokio::test]
+    async fn it_matches_the_in_memory_signer() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let pk = PublicKey::from_secret_key(
+            SECP256K1,
+            &SecretKey::new(&mut secp256k1::rand::thread_rng()),
+        );
+
+        let signer = RemoteSigner::connect(stand_in(ls_sk, usize::MAX).await, TIMEOUT)
+            .await
+            .unwrap();
+        let in_memory = InMemorySigner::new(ls_sk);
+
+        assert_eq! { signer.node_id(), NodeSigner::node_id(&in_memory) };
+        assert_eq! {
+            *signer.ecdh(&pk).await.unwrap(),
+            *NodeSigner::ecdh(&in_memory, &pk).unwrap()
+        };
+    }
+
+    #[tokio::test]
+    async fn it_performs_the_handshake_with_the_remote_key() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let signer = RemoteSigner::connect(stand_in(ls_sk, usize::MAX).await, TIMEOUT)
+            .await
+            .unwrap();
+
+        let (mut client_stream, mut server_stream) = tokio::io::duplex(1024);
+        let timeouts = HandshakeTimeouts::default();
+
+        let (client_proto, server_proto) =
+            tokio::join!(
+                ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk))
+                    .perform_handshake(&mut client_stream, &signer, &timeouts),
+                ServerProtocol::new(rs_sk).accept_handshake(&mut server_stream, &timeouts),
+            );
+
+        let mut client_proto = client_proto.unwrap();
+        let (mut server_proto, remote_pk) = server_proto.unwrap();
+
+        assert_eq! { remote_pk, PublicKey::from_secret_key(SECP256K1, &ls_sk) };
+
+        client_proto
+            .send_message(&mut client_stream, b"hello")
+            .await
+            .unwrap();
+
+        assert_eq! { server_proto.read_message(&mut server_stream).await.unwrap(), b"hello" };
+    }
+
+    #[tokio::test]
+    async fn it_fails_when_the_signer_rejects_the_request() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        // Only the node id request is answered.
+        let signer = RemoteSigner::connect(stand_in(ls_sk, 1).await, TIMEOUT)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            signer.ecdh(&pk).await,
+            Err(SignerError::Rejected(1))
+        ));
+
+        assert!(matches!(
+            RemoteSigner::connect(stand_in(ls_sk, 0).await, TIMEOUT).await,
+            Err(SignerError::Rejected(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_reconnects_after_a_cancelled_request() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        let signer = RemoteSigner::connect(stand_in(ls_sk, usize::MAX).await, TIMEOUT)
+            .await
+            .unwrap();
+
+        // The request is cancelled before its response is read.
+        let mut ecdh = Box::pin(signer.ecdh(&pk));
+        assert!(futures::poll!(ecdh.as_mut()).is_pending());
+        drop(ecdh);
+
+        assert!(signer.stream.lock().await.is_none());
+
+        assert_eq! {
+            *signer.ecdh(&pk).await.unwrap(),
+            *NodeSigner::ecdh(&InMemorySigner::new(ls_sk), &pk).unwrap()
+        };
+    }
+
+    #[tokio::test]
+    async fn it_only_connects_to_a_loopback_address() {
+        let address = "192.0.2.1:9735".parse().unwrap();
+
+        assert!(matches!(
+            RemoteSigner::connect(address, TIMEOUT).await,
+            Err(SignerError::NonLoopbackAddress(a)) if a == address
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_checks_the_node_id_after_reconnecting() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        let mut signer = RemoteSigner::connect(stand_in(ls_sk, usize::MAX).await, TIMEOUT)
+            .await
+            .unwrap();
+
+        // The connection is lost, and a signer with another key takes the address over.
+        let other_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        signer.address = stand_in(other_sk, usize::MAX).await;
+        signer.stream.lock().await.take();
+
+        assert!(matches!(
+            signer.ecdh(&pk).await,
+            Err(SignerError::UnexpectedNodeId { hex })
+                if hex == hex::encode(PublicKey::from_secret_key(SECP256K1, &other_sk).serialize())
+        ));
+        assert!(signer.stream.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn it_times_out_when_the_signer_stalls() {
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let pk = PublicKey::from_secret_key(SECP256K1, &ls_sk);
+
+        let signer = RemoteSigner::connect(stalling(ls_sk).await, Duration::from_millis(100))
+            .await
+            .unwrap();
+
+        assert!(matches!(signer.ecdh(&pk).await, Err(SignerError::Timeout)));
+
+        // The stalled connection is dropped, so that its late response is never read.
+        assert!(signer.stream.lock().await.is_none());
+    }
+}
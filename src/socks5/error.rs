@@ -0,0 +1,39 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Socks5Error {
+    #[error("IO error")]
+    IoError { source: eyre::Report },
+
+    #[error("Tor onion services can only be reached through a proxy")]
+    ProxyRequired,
+
+    #[error(
+        "The '{0}' is not a valid proxy. Expected format: [<username>:<password>@]<ip>:<port>"
+    )]
+    InvalidProxy(String),
+
+    #[error("The {0} is too long, at most 255 bytes are allowed")]
+    FieldTooLong(&'static str),
+
+    #[error("The proxy replied with the unsupported SOCKS version '{0}'")]
+    UnsupportedVersion(u8),
+
+    #[error("The proxy does not accept the offered authentication method")]
+    NoAcceptableMethod,
+
+    #[error("The proxy rejected the username and password")]
+    AuthenticationFailed,
+
+    #[error("The proxy failed to connect: {0}")]
+    ConnectionFailed(&'static str),
+
+    #[error("The proxy replied with the unknown address type '{0}'")]
+    UnknownAddressType(u8),
+}
+
+impl From<std::io::Error> for Socks5Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError {
+            source: eyre::Report::new(e),
+        }
+    }
+}
@@ -0,0 +1,513 @@
+//! This module defines a SOCKS5 client, used to reach remote nodes through a proxy such as Tor.
+//!
+//! Spec: <https://datatracker.ietf.org/doc/html/rfc1928>
+//!       <https://datatracker.ietf.org/doc/html/rfc1929>
+
+mod error;
+
+pub use self::error::Socks5Error;
+
+use crate::bolt_7::address::{Address, Host};
+use std::{fmt, net::SocketAddr, str::FromStr};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// The version of the SOCKS protocol.
+const VERSION: u8 = 5;
+
+/// The version of the username and password authentication.
+const AUTH_VERSION: u8 = 1;
+
+/// The authentication method that does not require any authentication.
+const NO_AUTH: u8 = 0;
+
+/// The authentication method with a username and a password.
+const USERNAME_PASSWORD: u8 = 2;
+
+/// The command that opens a TCP connection to the target.
+const CONNECT: u8 = 1;
+
+const ATYP_IPV4: u8 = 1;
+const ATYP_DOMAIN_NAME: u8 = 3;
+const ATYP_IPV6: u8 = 4;
+
+/// A SOCKS5 proxy that opens the connections to the remote nodes.
+#[derive(Debug, Clone)]
+pub struct Socks5Proxy {
+    /// The address of the proxy.
+    address: SocketAddr,
+
+    /// The credentials to authenticate with, if any.
+    credentials: Option<Credentials>,
+}
+
+/// The username and password to authenticate with the proxy.
+///
+/// Tor does not check them, but routes the connections with distinct credentials
+/// through distinct circuits, which isolates the streams from each other.
+#[derive(Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl Socks5Proxy {
+    /// Creates a proxy, which authenticates with the credentials when they are provided.
+    pub fn new(address: SocketAddr, credentials: Option<Credentials>) -> Self {
+        Self {
+            address,
+            credentials,
+        }
+    }
+
+    /// Opens a connection to the target through the proxy.
+    pub async fn connect(&self, target: &Address) -> Result<TcpStream, Socks5Error> {
+        let mut stream = TcpStream::connect(self.address).await?;
+
+        self.handshake(&mut stream, target).await?;
+
+        Ok(stream)
+    }
+
+    // Negotiates the authentication method, authenticates if needed,
+    // and asks the proxy to connect to the target.
+    async fn handshake(
+        &self,
+        stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+        target: &Address,
+    ) -> Result<(), Socks5Error> {
+        let method = match self.credentials {
+            Some(_) => USERNAME_PASSWORD,
+            None => NO_AUTH,
+        };
+
+        stream.write_all(&[VERSION, 1, method]).await?;
+
+        let [version, selected] = read_array(stream).await?;
+
+        if version != VERSION {
+            return Err(Socks5Error::UnsupportedVersion(version));
+        }
+
+        // The proxy selects 0xff when the offered method is not acceptable.
+        if selected != method {
+            return Err(Socks5Error::NoAcceptableMethod);
+        }
+
+        if let Some(credentials) = &self.credentials {
+            authenticate(stream, credentials).await?;
+        }
+
+        let mut request = vec![VERSION, CONNECT, 0];
+
+        match &target.host {
+            Host::Ipv4(ip) => {
+                request.push(ATYP_IPV4);
+                request.extend_from_slice(&ip.octets());
+            }
+            Host::Ipv6(ip) => {
+                request.push(ATYP_IPV6);
+                request.extend_from_slice(&ip.octets());
+            }
+            // The proxy resolves the hostnames and the onion addresses itself.
+            host @ (Host::TorV3(_) | Host::Hostname(_)) => {
+                request.push(ATYP_DOMAIN_NAME);
+                write_u8_prefixed(&mut request, "hostname", host.to_string().as_bytes())?;
+            }
+        }
+
+        request.extend_from_slice(&target.port.to_be_bytes());
+
+        stream.write_all(&request).await?;
+
+        // The reply ends with the address the proxy is bound to, which is not needed.
+        let [version, reply, _, atyp] = read_array(stream).await?;
+
+        if version != VERSION {
+            return Err(Socks5Error::UnsupportedVersion(version));
+        }
+
+        if reply != 0 {
+            return Err(Socks5Error::ConnectionFailed(reply_message(reply)));
+        }
+
+        let len = match atyp {
+            ATYP_IPV4 => 4,
+            ATYP_IPV6 => 16,
+            ATYP_DOMAIN_NAME => stream.read_u8().await? as usize,
+            x => return Err(Socks5Error::UnknownAddressType(x)),
+        };
+
+        let mut bound_address = vec![0; len + 2];
+        stream.read_exact(&mut bound_address).await?;
+
+        Ok(())
+    }
+}
+
+impl FromStr for Socks5Proxy {
+    type Err = Socks5Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (credentials, address) = match s.rsplit_once('@') {
+            Some((credentials, address)) => {
+                let (username, password) = credentials
+                    .split_once(':')
+                    .ok_or_else(|| Socks5Error::InvalidProxy(s.to_owned()))?;
+
+                let credentials = Credentials {
+                    username: username.to_owned(),
+                    password: password.to_owned(),
+                };
+
+                (Some(credentials), address)
+            }
+            None => (None, s),
+        };
+
+        let address = address
+            .parse()
+            .map_err(|_| Socks5Error::InvalidProxy(s.to_owned()))?;
+
+        Ok(Self::new(address, credentials))
+    }
+}
+
+// Authenticates with the username and password.
+async fn authenticate(
+    stream: &mut (impl AsyncRead + AsyncWrite + Unpin),
+    credentials: &Credentials,
+) -> Result<(), Socks5Error> {
+    let mut request = vec![AUTH_VERSION];
+    write_u8_prefixed(&mut request, "username", credentials.username.as_bytes())?;
+    write_u8_prefixed(&mut request, "password", credentials.password.as_bytes())?;
+
+    stream.write_all(&request).await?;
+
+    let [_, status] = read_array(stream).await?;
+
+    if status != 0 {
+        return Err(Socks5Error::AuthenticationFailed);
+    }
+
+    Ok(())
+}
+
+// Writes a 1-byte length followed by the bytes passed.
+fn write_u8_prefixed(buf: &mut Vec<u8>, field: &'static str, x: &[u8]) -> Result<(), Socks5Error> {
+    let len = u8::try_from(x.len()).map_err(|_| Socks5Error::FieldTooLong(field))?;
+
+    buf.push(len);
+    buf.extend_from_slice(x);
+
+    Ok(())
+}
+
+// Reads exactly `N` bytes.
+async fn read_array<const N: usize>(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> Result<[u8; N], Socks5Error> {
+    let mut buf = [0u8; N];
+    stream.read_exact(&mut buf).await?;
+
+    Ok(buf)
+}
+
+// Returns the description of a reply code of the proxy.
+fn reply_message(reply: u8) -> &'static str {
+    match reply {
+        1 => "general SOCKS server failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bolt_8::protocol::{ClientProtocol, HandshakeTimeouts, ServerProtocol},
+        signer::InMemorySigner,
+    };
+    use secp256k1::{PublicKey, SecretKey, SECP256K1};
+    use tokio::net::TcpListener;
+
+    const ONION: &str = "duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzczad.onion";
+
+    // The addresses the proxy may reply it is bound to, with their type and port.
+    const BOUND_IPV4: &[u8] = &[ATYP_IPV4, 127, 0, 0, 1, 0x23, 0x29];
+    const BOUND_IPV6: &[u8] = &[
+        ATYP_IPV6, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0x23, 0x29,
+    ];
+    const BOUND_DOMAIN_NAME: &[u8] = &[
+        ATYP_DOMAIN_NAME,
+        9,
+        b'l',
+        b'o',
+        b'c',
+        b'a',
+        b'l',
+        b'h',
+        b'o',
+        b's',
+        b't',
+        0x23,
+        0x29,
+    ];
+
+    // What the proxy received from the client.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Received {
+        methods: Vec<u8>,
+        credentials: Option<(String, String)>,
+        target: Address,
+    }
+
+    // Serves a single connection as a SOCKS5 proxy that accepts any target and replies with
+    // the status and the bound address passed, then hands the stream over as if it was
+    // connected to the target.
+    async fn stand_in(
+        listener: &TcpListener,
+        auth_status: u8,
+        reply: u8,
+        bound: &[u8],
+    ) -> Result<(Received, TcpStream), Socks5Error> {
+        let (mut stream, _) = listener.accept().await?;
+
+        let [_, nmethods] = read_array(&mut stream).await?;
+        let mut methods = vec![0; nmethods as usize];
+        stream.read_exact(&mut methods).await?;
+
+        stream.write_all(&[VERSION, methods[0]]).await?;
+
+        let mut credentials = None;
+
+        if methods[0] == USERNAME_PASSWORD {
+            let [_, len] = read_array(&mut stream).await?;
+            let mut username = vec![0; len as usize];
+            stream.read_exact(&mut username).await?;
+
+            let len = stream.read_u8().await?;
+            let mut password = vec![0; len as usize];
+            stream.read_exact(&mut password).await?;
+
+            credentials = Some((
+                String::from_utf8(username).unwrap(),
+                String::from_utf8(password).unwrap(),
+            ));
+
+            stream.write_all(&[AUTH_VERSION, auth_status]).await?;
+        }
+
+        let [_, _, _, atyp] = read_array(&mut stream).await?;
+
+        let host = match atyp {
+            ATYP_IPV4 => Host::Ipv4(read_array::<4>(&mut stream).await?.into()),
+            ATYP_IPV6 => Host::Ipv6(read_array::<16>(&mut stream).await?.into()),
+            ATYP_DOMAIN_NAME => {
+                let len = stream.read_u8().await?;
+                let mut host = vec![0; len as usize];
+                stream.read_exact(&mut host).await?;
+
+                String::from_utf8(host).unwrap().parse().unwrap()
+            }
+            x => return Err(Socks5Error::UnknownAddressType(x)),
+        };
+        let port = stream.read_u16().await?;
+
+        stream.write_all(&[VERSION, reply, 0]).await?;
+        stream.write_all(bound).await?;
+
+        let received = Received {
+            methods,
+            credentials,
+            target: Address { host, port },
+        };
+
+        Ok((received, stream))
+    }
+
+    #[tokio::test]
+    async fn it_performs_the_handshake_through_the_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Socks5Proxy::new(listener.local_addr().unwrap(), None);
+
+        let ls_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+        let rs_sk = SecretKey::new(&mut secp256k1::rand::thread_rng());
+
+        let target = format!("{ONION}:9735").parse::<Address>().unwrap();
+        let timeouts = HandshakeTimeouts::default();
+
+        let client = async {
+            let mut stream = proxy.connect(&target).await.unwrap();
+
+            ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk))
+                .perform_handshake(&mut stream, InMemorySigner::new(ls_sk), &timeouts)
+                .await
+                .unwrap();
+        };
+
+        let server = async {
+            let (received, mut stream) = stand_in(&listener, 0, 0, BOUND_IPV4).await.unwrap();
+
+            let (_, remote_pk) = ServerProtocol::new(rs_sk)
+                .accept_handshake(&mut stream, &timeouts)
+                .await
+                .unwrap();
+
+            (received, remote_pk)
+        };
+
+        let (_, (received, remote_pk)) = tokio::join!(client, server);
+
+        assert_eq! {
+            received,
+            Received {
+                methods: vec![NO_AUTH],
+                credentials: None,
+                target,
+            }
+        };
+        assert_eq! { remote_pk, PublicKey::from_secret_key(SECP256K1, &ls_sk) };
+    }
+
+    #[tokio::test]
+    async fn it_authenticates_with_the_credentials() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("alice:secret@{}", listener.local_addr().unwrap())
+            .parse::<Socks5Proxy>()
+            .unwrap();
+
+        let target = "node.example.com:9736".parse::<Address>().unwrap();
+
+        let (result, stand_in) = tokio::join!(
+            proxy.connect(&target),
+            stand_in(&listener, 0, 0, BOUND_IPV4)
+        );
+
+        assert!(result.is_ok());
+
+        let (received, _) = stand_in.unwrap();
+
+        assert_eq! {
+            received,
+            Received {
+                methods: vec![USERNAME_PASSWORD],
+                credentials: Some(("alice".to_owned(), "secret".to_owned())),
+                target,
+            }
+        };
+    }
+
+    #[tokio::test]
+    async fn it_connects_to_every_type_of_target() {
+        let targets = [
+            "203.0.113.7:9735".to_owned(),
+            "[2001:db8::7]:9736".to_owned(),
+            "node.example.com:9737".to_owned(),
+            format!("{ONION}:9738"),
+        ];
+
+        for target in targets {
+            for bound in [BOUND_IPV4, BOUND_IPV6, BOUND_DOMAIN_NAME] {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let proxy = Socks5Proxy::new(listener.local_addr().unwrap(), None);
+
+                let target = target.parse::<Address>().unwrap();
+
+                let (result, stand_in) =
+                    tokio::join!(proxy.connect(&target), stand_in(&listener, 0, 0, bound));
+
+                let mut client_stream = result.unwrap();
+                let (received, mut proxy_stream) = stand_in.unwrap();
+
+                assert_eq! { received.target, target, "bound to {bound:?}" };
+
+                // The whole reply is consumed, so the stream starts with the bytes of the target.
+                proxy_stream.write_all(b"hello").await.unwrap();
+
+                let mut buf = [0u8; 5];
+                client_stream.read_exact(&mut buf).await.unwrap();
+                assert_eq! { &buf, b"hello", "{target} bound to {bound:?}" };
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_fails_when_the_proxy_rejects_the_credentials() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("alice:wrong@{}", listener.local_addr().unwrap())
+            .parse::<Socks5Proxy>()
+            .unwrap();
+
+        let target = "node.example.com:9735".parse::<Address>().unwrap();
+
+        let (result, _) = tokio::join!(
+            proxy.connect(&target),
+            stand_in(&listener, 1, 0, BOUND_IPV4)
+        );
+
+        assert!(matches!(result, Err(Socks5Error::AuthenticationFailed)));
+    }
+
+    #[tokio::test]
+    async fn it_fails_when_the_proxy_cannot_connect_to_the_target() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = Socks5Proxy::new(listener.local_addr().unwrap(), None);
+
+        let target = format!("{ONION}:9735").parse::<Address>().unwrap();
+
+        let (result, _) = tokio::join!(
+            proxy.connect(&target),
+            stand_in(&listener, 0, 4, BOUND_IPV4)
+        );
+
+        assert!(matches!(
+            result,
+            Err(Socks5Error::ConnectionFailed("host unreachable"))
+        ));
+    }
+
+    #[test]
+    fn it_parses_the_proxy() {
+        let proxy = "127.0.0.1:9050".parse::<Socks5Proxy>().unwrap();
+
+        assert_eq! { proxy.address, "127.0.0.1:9050".parse().unwrap() };
+        assert!(proxy.credentials.is_none());
+
+        let proxy = "user:pass:word@[::1]:9050".parse::<Socks5Proxy>().unwrap();
+        let credentials = proxy.credentials.unwrap();
+
+        assert_eq! { proxy.address, "[::1]:9050".parse().unwrap() };
+        assert_eq! { credentials.username, "user" };
+        assert_eq! { credentials.password, "pass:word" };
+        assert_eq! {
+            format!("{credentials:?}"),
+            r#"Credentials { username: "user", password: "[REDACTED]" }"#
+        };
+
+        for s in ["127.0.0.1", "localhost:9050", "user@127.0.0.1:9050"] {
+            assert!(matches!(
+                s.parse::<Socks5Proxy>(),
+                Err(Socks5Error::InvalidProxy(_))
+            ));
+        }
+    }
+}
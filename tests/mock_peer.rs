@@ -0,0 +1,72 @@
+use lightning_client::{
+    bolt_1::{
+        message::{Init, Message, Warning},
+        network::Network,
+    },
+    bolt_8::protocol::{
+        mock_peer::{MockPeer, Step, HANDSHAKE},
+        ClientProtocol, HandshakeTimeouts, Keepalive, ProtocolError,
+    },
+    signer::InMemorySigner,
+};
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use tokio::net::TcpStream;
+
+fn random_key() -> SecretKey {
+    SecretKey::new(&mut secp256k1::rand::thread_rng())
+}
+
+fn warning(data: &[u8]) -> Message {
+    Message::Warning(Warning {
+        channel_id: [0; 32],
+        data: data.to_vec(),
+    })
+}
+
+// Connects to a mock peer following the script after the handshake and the init messages,
+// and returns the next message read from it.
+async fn session(script: impl IntoIterator<Item = Step>) -> Result<Message, ProtocolError> {
+    let rs_sk = random_key();
+    let chain_hash = Network::Bitcoin.chain_hash();
+
+    let script = HANDSHAKE
+        .into_iter()
+        .chain([Step::Read, Step::Send(Message::Init(Init::default()))])
+        .chain(script);
+    let (address, _peer) = MockPeer::new(rs_sk, script).listen().await;
+
+    let mut stream = TcpStream::connect(address).await.unwrap();
+
+    let mut client_proto = ClientProtocol::new(PublicKey::from_secret_key(SECP256K1, &rs_sk))
+        .perform_handshake(
+            &mut stream,
+            InMemorySigner::new(random_key()),
+            &HandshakeTimeouts::default(),
+        )
+        .await?;
+
+    client_proto
+        .exchange_init(&mut stream, &Init::default(), &chain_hash)
+        .await?;
+
+    client_proto
+        .read_message_with_keepalive(&mut stream, &mut Keepalive::default())
+        .await
+}
+
+#[tokio::test]
+async fn it_reads_the_messages_of_a_mock_peer() {
+    let message = session([Step::Send(warning(b"hello")), Step::Stall]).await;
+
+    assert_eq! { message.unwrap(), warning(b"hello") };
+}
+
+#[tokio::test]
+async fn it_fails_on_a_message_of_a_mock_peer_with_a_bad_mac() {
+    let message = session([Step::SendBadMac(b"hello".to_vec()), Step::Stall]).await;
+
+    assert!(matches!(
+        message,
+        Err(ProtocolError::CryptographyFailure { .. })
+    ));
+}